@@ -0,0 +1,123 @@
+//! Positions and rise/set times of the sun and moon, worked out locally from a location's
+//! coordinates so they don't depend on the forecast provider or a network connection.
+//!
+//! The sun follows NOAA's solar calculator and the moon the low precision series in the
+//! Astronomical Almanac, both good to a minute or two for rise and set times.
+
+pub(crate) mod moon;
+pub(crate) mod sun;
+
+use chrono::{DateTime, Utc};
+
+pub(crate) use moon::{moon, moon_times, next_full_moon, next_new_moon, MoonPhase};
+pub(crate) use sun::sun_times;
+
+/// Julian day of the J2000.0 epoch, which the series below count from.
+const J2000: f64 = 2451545.0;
+
+/// Where on Earth the sky is seen from, in degrees with north and east positive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Observer {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Observer {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// The altitude in degrees of a body at the given equatorial coordinates.
+    fn altitude(&self, at: DateTime<Utc>, right_ascension: f64, declination: f64) -> f64 {
+        let hour_angle = sidereal_time(at) + self.longitude - right_ascension;
+        let latitude = self.latitude.to_radians();
+        let declination = declination.to_radians();
+        (latitude.sin() * declination.sin()
+            + latitude.cos() * declination.cos() * hour_angle.to_radians().cos())
+        .asin()
+        .to_degrees()
+    }
+}
+
+/// When something crosses the horizon, or a twilight's altitude, on the way up and down.
+///
+/// Either is `None` when it doesn't happen that day, such as the sun at midsummer in the Arctic.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct RiseSet {
+    pub rise: Option<DateTime<Utc>>,
+    pub set: Option<DateTime<Utc>>,
+}
+
+fn julian_day(at: DateTime<Utc>) -> f64 {
+    at.timestamp_millis() as f64 / 86_400_000.0 + 2440587.5
+}
+
+/// Julian centuries since J2000.0.
+fn centuries(at: DateTime<Utc>) -> f64 {
+    (julian_day(at) - J2000) / 36525.0
+}
+
+/// Greenwich mean sidereal time in degrees.
+fn sidereal_time(at: DateTime<Utc>) -> f64 {
+    let t = centuries(at);
+    (280.46061837 + 360.98564736629 * (julian_day(at) - J2000) + 0.000387933 * t * t
+        - t * t * t / 38710000.0)
+        .rem_euclid(360.0)
+}
+
+/// Mean obliquity of the ecliptic in degrees.
+fn obliquity(t: f64) -> f64 {
+    23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001813))) / 60.0) / 60.0
+}
+
+/// Right ascension and declination in degrees from ecliptic longitude and latitude.
+fn equatorial(longitude: f64, latitude: f64, obliquity: f64) -> (f64, f64) {
+    let (longitude, latitude, obliquity) = (
+        longitude.to_radians(),
+        latitude.to_radians(),
+        obliquity.to_radians(),
+    );
+    let right_ascension = (longitude.sin() * obliquity.cos() - latitude.tan() * obliquity.sin())
+        .atan2(longitude.cos());
+    let declination = (latitude.sin() * obliquity.cos()
+        + latitude.cos() * obliquity.sin() * longitude.sin())
+    .asin();
+    (
+        right_ascension.to_degrees().rem_euclid(360.0),
+        declination.to_degrees(),
+    )
+}
+
+/// Sine of an angle in degrees, as the series are all written in degrees.
+fn sin(degrees: f64) -> f64 {
+    degrees.to_radians().sin()
+}
+
+fn cos(degrees: f64) -> f64 {
+    degrees.to_radians().cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn counts_julian_days() {
+        let epoch = Utc.with_ymd_and_hms(2000, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(julian_day(epoch), J2000);
+        // Meeus, Astronomical Algorithms, example 7.a
+        let sputnik = Utc.with_ymd_and_hms(1957, 10, 4, 19, 26, 24).unwrap();
+        assert!((julian_day(sputnik) - 2436116.31).abs() < 0.001);
+    }
+
+    #[test]
+    fn keeps_sidereal_time() {
+        // Meeus example 12.a, 13h 10m 46.3668s
+        let at = Utc.with_ymd_and_hms(1987, 4, 10, 0, 0, 0).unwrap();
+        assert!((sidereal_time(at) - 197.693195).abs() < 0.0001);
+    }
+}
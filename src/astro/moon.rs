@@ -0,0 +1,314 @@
+use super::{centuries, cos, equatorial, obliquity, sin, sun, Observer, RiseSet};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta, Utc};
+
+/// How often the moon's altitude is sampled when looking for it crossing the horizon.
+const SEARCH_STEP_MINUTES: i64 = 10;
+
+/// The eight phases of the moon, each centred on its elongation from the sun.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+const PHASES: [MoonPhase; 8] = [
+    MoonPhase::New,
+    MoonPhase::WaxingCrescent,
+    MoonPhase::FirstQuarter,
+    MoonPhase::WaxingGibbous,
+    MoonPhase::Full,
+    MoonPhase::WaningGibbous,
+    MoonPhase::LastQuarter,
+    MoonPhase::WaningCrescent,
+];
+
+impl MoonPhase {
+    fn from_elongation(elongation: f64) -> Self {
+        PHASES[(elongation / 45.0).round() as usize % PHASES.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MoonPhase::New => "New moon",
+            MoonPhase::WaxingCrescent => "Waxing crescent",
+            MoonPhase::FirstQuarter => "First quarter",
+            MoonPhase::WaxingGibbous => "Waxing gibbous",
+            MoonPhase::Full => "Full moon",
+            MoonPhase::WaningGibbous => "Waning gibbous",
+            MoonPhase::LastQuarter => "Last quarter",
+            MoonPhase::WaningCrescent => "Waning crescent",
+        }
+    }
+}
+
+/// The moon as seen at a moment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Moon {
+    pub phase: MoonPhase,
+    /// How far the moon's longitude is ahead of the sun's, 0° at new moon and 180° at full
+    pub elongation: f64,
+    /// Fraction of the disc that is lit, from 0 to 1
+    pub illumination: f64,
+}
+
+/// Where the moon is at a moment, in degrees.
+struct Position {
+    longitude: f64,
+    latitude: f64,
+    /// Horizontal parallax, which is large enough for the moon to shift its rise and set
+    parallax: f64,
+}
+
+fn position(at: DateTime<Utc>) -> Position {
+    let t = centuries(at);
+    let longitude = 218.32 + 481267.881 * t + 6.29 * sin(135.0 + 477198.87 * t)
+        - 1.27 * sin(259.3 - 413335.36 * t)
+        + 0.66 * sin(235.7 + 890534.22 * t)
+        + 0.21 * sin(269.9 + 954397.74 * t)
+        - 0.19 * sin(357.5 + 35999.05 * t)
+        - 0.11 * sin(186.5 + 966404.03 * t);
+    let latitude = 5.13 * sin(93.3 + 483202.02 * t) + 0.28 * sin(228.2 + 960400.89 * t)
+        - 0.28 * sin(318.3 + 6003.15 * t)
+        - 0.17 * sin(217.6 - 407332.21 * t);
+    let parallax = 0.9508
+        + 0.0518 * cos(135.0 + 477198.87 * t)
+        + 0.0095 * cos(259.3 - 413335.36 * t)
+        + 0.0078 * cos(235.7 + 890534.22 * t)
+        + 0.0028 * cos(269.9 + 954397.74 * t);
+    Position {
+        longitude: longitude.rem_euclid(360.0),
+        latitude,
+        parallax,
+    }
+}
+
+/// The moon's phase and illumination at a moment.
+pub(crate) fn moon(at: DateTime<Utc>) -> Moon {
+    let moon = position(at);
+    let sun = sun::position(at);
+    let elongation = (moon.longitude - sun.longitude).rem_euclid(360.0);
+    // The sun is far enough away that the phase angle is all but the supplement of the elongation
+    let separation = (cos(moon.latitude) * cos(elongation)).acos().to_degrees();
+    Moon {
+        phase: MoonPhase::from_elongation(elongation),
+        elongation,
+        illumination: (1.0 - cos(separation)) / 2.0,
+    }
+}
+
+/// When the moon next reaches an elongation from the sun, such as 180° for full moon.
+///
+/// The elongation always grows, by about 12° a day, so step forward until it passes the target
+/// and then narrow down on it.
+fn next_elongation(after: DateTime<Utc>, target: f64) -> DateTime<Utc> {
+    // How far short of the target the moon is, from 0 up to a full lap
+    let short_of = |at| (target - moon(at).elongation).rem_euclid(360.0);
+    let step = TimeDelta::hours(6);
+
+    let mut before = after;
+    let mut next = after + step;
+    // Passing the target wraps from just short of it to nearly a lap short
+    while short_of(next) <= short_of(before) {
+        before = next;
+        next = before + step;
+    }
+    while next - before > TimeDelta::seconds(1) {
+        let middle = before + (next - before) / 2;
+        if short_of(middle) <= short_of(before) {
+            before = middle;
+        } else {
+            next = middle;
+        }
+    }
+    before
+}
+
+pub(crate) fn next_new_moon(after: DateTime<Utc>) -> DateTime<Utc> {
+    next_elongation(after, 0.0)
+}
+
+pub(crate) fn next_full_moon(after: DateTime<Utc>) -> DateTime<Utc> {
+    next_elongation(after, 180.0)
+}
+
+/// The moon's altitude above where it appears to rise and set, allowing for parallax and refraction.
+fn altitude_above_horizon(observer: &Observer, at: DateTime<Utc>) -> f64 {
+    let moon = position(at);
+    let (right_ascension, declination) =
+        equatorial(moon.longitude, moon.latitude, obliquity(centuries(at)));
+    observer.altitude(at, right_ascension, declination) - (0.7275 * moon.parallax - 0.5667)
+}
+
+/// Narrows down when the altitude changes sign between two moments to the nearest second.
+fn refine(
+    observer: &Observer,
+    mut before: DateTime<Utc>,
+    mut after: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let rising = altitude_above_horizon(observer, before) < 0.0;
+    while after - before > TimeDelta::seconds(1) {
+        let middle = before + (after - before) / 2;
+        if (altitude_above_horizon(observer, middle) < 0.0) == rising {
+            before = middle;
+        } else {
+            after = middle;
+        }
+    }
+    before
+}
+
+/// Moonrise and moonset during a local calendar day.
+///
+/// The moon rises about 50 minutes later each day, so roughly once a month it doesn't rise or
+/// doesn't set on a given day.
+pub(crate) fn moon_times(observer: Observer, date: NaiveDate, offset: FixedOffset) -> RiseSet {
+    let start = date
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_local_timezone(offset)
+        .unwrap()
+        .with_timezone(&Utc);
+    let step = TimeDelta::minutes(SEARCH_STEP_MINUTES);
+
+    let mut times = RiseSet {
+        rise: None,
+        set: None,
+    };
+    let mut at = start;
+    let mut above = altitude_above_horizon(&observer, at) >= 0.0;
+    while at < start + TimeDelta::days(1) {
+        let next = at + step;
+        let next_above = altitude_above_horizon(&observer, next) >= 0.0;
+        if next_above != above {
+            let crossing = refine(&observer, at, next);
+            let slot = if next_above {
+                &mut times.rise
+            } else {
+                &mut times.set
+            };
+            if crossing < start + TimeDelta::days(1) {
+                slot.get_or_insert(crossing);
+            }
+        }
+        at = next;
+        above = next_above;
+    }
+    times
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn finds_the_moons_position() {
+        // Meeus example 47.a, 1992 April 12 at 0h: λ 133.163°, β -3.229°, π 0.991°
+        let position = position(Utc.with_ymd_and_hms(1992, 4, 12, 0, 0, 0).unwrap());
+        assert!((position.longitude - 133.163).abs() < 0.3);
+        assert!((position.latitude - -3.229).abs() < 0.2);
+        assert!((position.parallax - 0.991).abs() < 0.01);
+    }
+
+    #[test]
+    fn lights_the_moon() {
+        // Meeus example 48.a finds 0.6786 of the disc lit for the same moment
+        let moon = moon(Utc.with_ymd_and_hms(1992, 4, 12, 0, 0, 0).unwrap());
+        assert!((moon.illumination - 0.6786).abs() < 0.01);
+    }
+
+    #[test]
+    fn names_the_phases_of_a_lunation() {
+        // New 4 August 2024 11:13, first quarter 12 August 15:19, full 19 August 18:26 and
+        // last quarter 26 August 09:26, all UTC
+        for (day, hour, minute, phase) in [
+            (4, 11, 13, MoonPhase::New),
+            (12, 15, 19, MoonPhase::FirstQuarter),
+            (19, 18, 26, MoonPhase::Full),
+            (26, 9, 26, MoonPhase::LastQuarter),
+        ] {
+            let at = Utc.with_ymd_and_hms(2024, 8, day, hour, minute, 0).unwrap();
+            let moon = moon(at);
+            assert_eq!(moon.phase, phase);
+            let expected = PHASES.iter().position(|p| *p == phase).unwrap() as f64 * 45.0;
+            let error = (moon.elongation - expected + 180.0).rem_euclid(360.0) - 180.0;
+            assert!(error.abs() < 1.0, "{:?} is {}° out", phase, error);
+        }
+
+        let full = moon(Utc.with_ymd_and_hms(2024, 8, 19, 18, 26, 0).unwrap());
+        assert!(full.illumination > 0.99);
+        let new = moon(Utc.with_ymd_and_hms(2024, 8, 4, 11, 13, 0).unwrap());
+        assert!(new.illumination < 0.01);
+        let crescent = moon(Utc.with_ymd_and_hms(2024, 8, 8, 0, 0, 0).unwrap());
+        assert_eq!(crescent.phase, MoonPhase::WaxingCrescent);
+    }
+
+    #[test]
+    fn finds_the_next_new_and_full_moons() {
+        let at = |month, day, hour, minute| {
+            Utc.with_ymd_and_hms(2024, month, day, hour, minute, 0)
+                .unwrap()
+        };
+        let within_an_hour = |found: DateTime<Utc>, expected: DateTime<Utc>| {
+            assert!(
+                (found - expected).num_minutes().abs() < 60,
+                "{} not {}",
+                found,
+                expected
+            );
+        };
+        // New 4 August 11:13 and 3 September 01:55, full 19 August 18:26 and 18 September 02:34
+        within_an_hour(next_new_moon(at(8, 1, 0, 0)), at(8, 4, 11, 13));
+        within_an_hour(next_full_moon(at(8, 1, 0, 0)), at(8, 19, 18, 26));
+        within_an_hour(next_new_moon(at(8, 5, 0, 0)), at(9, 3, 1, 55));
+        within_an_hour(next_full_moon(at(8, 20, 0, 0)), at(9, 18, 2, 34));
+    }
+
+    #[test]
+    fn rises_around_sunset_when_full() {
+        let london = Observer::new(51.5074, -0.1278);
+        let bst = FixedOffset::east_opt(3600).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 8, 19).unwrap();
+        let moon = moon_times(london, date, bst);
+        let sun = sun::sun_times(london, date);
+        let after_sunset = moon.rise.unwrap() - sun.daylight.set.unwrap();
+        assert!(after_sunset.num_minutes().abs() < 60, "{}", after_sunset);
+        // Having risen the evening before, it sets around sunrise. It was still half a day short of
+        // full then, so it set a little earlier.
+        let before_sunrise = sun.daylight.rise.unwrap() - moon.set.unwrap();
+        assert!(
+            (0..90).contains(&before_sunrise.num_minutes()),
+            "{}",
+            before_sunrise
+        );
+    }
+
+    #[test]
+    fn skips_a_moonrise_once_a_month() {
+        let london = Observer::new(51.5074, -0.1278);
+        let bst = FixedOffset::east_opt(3600).unwrap();
+        let days: Vec<_> = (1..=30)
+            .map(|day| {
+                let date = NaiveDate::from_ymd_opt(2024, 9, day).unwrap();
+                moon_times(london, date, bst)
+            })
+            .collect();
+        let without_rise = days.iter().filter(|times| times.rise.is_none()).count();
+        let without_set = days.iter().filter(|times| times.set.is_none()).count();
+        assert_eq!(without_rise, 1);
+        assert_eq!(without_set, 1);
+        // Each day's rise is later than the day before's, apart from wrapping past midnight
+        let rises: Vec<_> = days.iter().filter_map(|times| times.rise).collect();
+        let wraps = rises
+            .windows(2)
+            .filter(|pair| pair[1].with_timezone(&bst).time() < pair[0].with_timezone(&bst).time())
+            .count();
+        assert_eq!(wraps, 1);
+    }
+}
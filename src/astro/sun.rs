@@ -0,0 +1,276 @@
+use super::{centuries, cos, obliquity, sin, Observer, RiseSet};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+/// The sun's centre is this far below the horizon at sunrise, allowing for its radius and refraction.
+const SUNRISE_ZENITH: f64 = 90.833;
+const CIVIL_ZENITH: f64 = 96.0;
+const NAUTICAL_ZENITH: f64 = 102.0;
+const ASTRONOMICAL_ZENITH: f64 = 108.0;
+
+/// The sun's day at a location, all times in UTC.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct SunTimes {
+    #[allow(dead_code)]
+    pub solar_noon: DateTime<Utc>,
+    pub daylight: RiseSet,
+    /// While the sun is less than 6° below the horizon, light enough to see outdoors
+    #[allow(dead_code)]
+    pub civil: RiseSet,
+    /// Less than 12°, when the horizon is still visible at sea
+    #[allow(dead_code)]
+    pub nautical: RiseSet,
+    /// Less than 18°, beyond which the sky is fully dark
+    #[allow(dead_code)]
+    pub astronomical: RiseSet,
+}
+
+/// Where the sun is at a moment, in degrees.
+pub(super) struct Position {
+    /// Apparent ecliptic longitude
+    pub longitude: f64,
+    pub declination: f64,
+    /// Minutes that solar time is ahead of mean time
+    pub equation_of_time: f64,
+}
+
+pub(super) fn position(at: DateTime<Utc>) -> Position {
+    let t = centuries(at);
+    let mean_longitude = (280.46646 + t * (36000.76983 + t * 0.0003032)).rem_euclid(360.0);
+    let mean_anomaly = 357.52911 + t * (35999.05029 - 0.0001537 * t);
+    let eccentricity = 0.016708634 - t * (0.000042037 + 0.0000001267 * t);
+    let centre = sin(mean_anomaly) * (1.914602 - t * (0.004817 + 0.000014 * t))
+        + sin(2.0 * mean_anomaly) * (0.019993 - 0.000101 * t)
+        + sin(3.0 * mean_anomaly) * 0.000289;
+    let node = 125.04 - 1934.136 * t;
+    let longitude = mean_longitude + centre - 0.00569 - 0.00478 * sin(node);
+    let obliquity = obliquity(t) + 0.00256 * cos(node);
+    let declination = (sin(obliquity) * sin(longitude)).asin().to_degrees();
+
+    let y = (obliquity / 2.0).to_radians().tan().powi(2);
+    let equation_of_time = 4.0
+        * (y * sin(2.0 * mean_longitude) - 2.0 * eccentricity * sin(mean_anomaly)
+            + 4.0 * eccentricity * y * sin(mean_anomaly) * cos(2.0 * mean_longitude)
+            - 0.5 * y * y * sin(4.0 * mean_longitude)
+            - 1.25 * eccentricity * eccentricity * sin(2.0 * mean_anomaly))
+        .to_degrees();
+
+    Position {
+        longitude: longitude.rem_euclid(360.0),
+        declination,
+        equation_of_time,
+    }
+}
+
+fn after_midnight(date: NaiveDate, minutes: f64) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+    midnight + TimeDelta::milliseconds((minutes * 60_000.0).round() as i64)
+}
+
+/// When the sun is due south, or north in the southern hemisphere, in minutes after midnight UTC.
+fn noon_minutes(observer: &Observer, at: DateTime<Utc>) -> f64 {
+    720.0 - 4.0 * observer.longitude - position(at).equation_of_time
+}
+
+/// When the sun's centre reaches a zenith distance before or after noon.
+///
+/// The sun moves on during the day, so each estimate is refined with its position at the last one.
+fn crossing(
+    observer: &Observer,
+    date: NaiveDate,
+    zenith: f64,
+    morning: bool,
+) -> Option<DateTime<Utc>> {
+    let mut at = after_midnight(date, noon_minutes(observer, after_midnight(date, 720.0)));
+    for _ in 0..3 {
+        let declination = position(at).declination;
+        let cos_hour_angle = (cos(zenith) - sin(observer.latitude) * sin(declination))
+            / (cos(observer.latitude) * cos(declination));
+        if !(-1.0..=1.0).contains(&cos_hour_angle) {
+            return None;
+        }
+        let hour_angle = cos_hour_angle.acos().to_degrees();
+        let offset = if morning { -hour_angle } else { hour_angle };
+        at = after_midnight(date, noon_minutes(observer, at) + 4.0 * offset);
+    }
+    Some(at)
+}
+
+fn rise_set(observer: &Observer, date: NaiveDate, zenith: f64) -> RiseSet {
+    RiseSet {
+        rise: crossing(observer, date, zenith, true),
+        set: crossing(observer, date, zenith, false),
+    }
+}
+
+/// Sunrise, sunset, twilights and solar noon on a date.
+///
+/// The date is taken around the location's solar noon, which matches its calendar day wherever
+/// the local clock is within twelve hours of solar time.
+pub(crate) fn sun_times(observer: Observer, date: NaiveDate) -> SunTimes {
+    let estimate = after_midnight(date, noon_minutes(&observer, after_midnight(date, 720.0)));
+    SunTimes {
+        solar_noon: after_midnight(date, noon_minutes(&observer, estimate)),
+        daylight: rise_set(&observer, date, SUNRISE_ZENITH),
+        civil: rise_set(&observer, date, CIVIL_ZENITH),
+        nautical: rise_set(&observer, date, NAUTICAL_ZENITH),
+        astronomical: rise_set(&observer, date, ASTRONOMICAL_ZENITH),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone};
+
+    const LONDON: Observer = Observer {
+        latitude: 51.5074,
+        longitude: -0.1278,
+    };
+    // The location in the test forecast
+    const DYSERTH: Observer = Observer {
+        latitude: 53.3003,
+        longitude: -3.4126,
+    };
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// Formats a time as the almanac would, in local time to the minute.
+    fn local(at: Option<DateTime<Utc>>, offset_hours: i32) -> String {
+        let offset = FixedOffset::east_opt(offset_hours * 3600).unwrap();
+        let at = at.unwrap().with_timezone(&offset);
+        // Almanacs round to the nearest minute
+        (at + TimeDelta::seconds(30)).format("%H:%M").to_string()
+    }
+
+    fn within_minutes(at: Option<DateTime<Utc>>, offset_hours: i32, expected: &str, minutes: i64) {
+        let offset = FixedOffset::east_opt(offset_hours * 3600).unwrap();
+        let at = at.unwrap();
+        let expected = offset
+            .from_local_datetime(
+                &at.with_timezone(&offset)
+                    .date_naive()
+                    .and_time(chrono::NaiveTime::parse_from_str(expected, "%H:%M").unwrap()),
+            )
+            .unwrap();
+        let difference = (at - expected.with_timezone(&Utc)).num_seconds().abs();
+        assert!(
+            difference <= minutes * 60,
+            "{} is not within {} minutes of {}",
+            local(Some(at), offset_hours),
+            minutes,
+            expected.format("%H:%M")
+        );
+    }
+
+    #[test]
+    fn finds_the_suns_position() {
+        // Meeus example 25.a, 1992 October 13 at 0h: 199.90988° and -7.78507° to low accuracy
+        let position = position(Utc.with_ymd_and_hms(1992, 10, 13, 0, 0, 0).unwrap());
+        assert!((position.longitude - 199.909).abs() < 0.01);
+        assert!((position.declination - -7.785).abs() < 0.01);
+        // Meeus example 28.a gives 13m 42.6s for the same day
+        assert!((position.equation_of_time - 13.71).abs() < 0.1);
+    }
+
+    #[test]
+    fn agrees_with_the_bbc_sunrise_and_sunset() {
+        // Times from the test forecast, in BST
+        for (day, sunrise, sunset) in [
+            (date(2024, 8, 29), "06:19", "20:09"),
+            (date(2024, 9, 4), "06:29", "19:54"),
+            (date(2024, 9, 11), "06:41", "19:38"),
+        ] {
+            let times = sun_times(DYSERTH, day);
+            within_minutes(times.daylight.rise, 1, sunrise, 1);
+            within_minutes(times.daylight.set, 1, sunset, 1);
+        }
+    }
+
+    #[test]
+    fn matches_the_almanac_at_midsummer() {
+        // Sunrise 04:43, solar noon 13:02 and sunset 21:21 BST in London on 20 June 2024
+        let times = sun_times(LONDON, date(2024, 6, 20));
+        within_minutes(times.daylight.rise, 1, "04:43", 1);
+        within_minutes(Some(times.solar_noon), 1, "13:02", 1);
+        within_minutes(times.daylight.set, 1, "21:21", 1);
+        // Civil twilight 03:56 to 22:08
+        within_minutes(times.civil.rise, 1, "03:56", 2);
+        within_minutes(times.civil.set, 1, "22:08", 2);
+        // The sun never gets 18° below the horizon, so it never gets properly dark
+        assert_eq!(times.astronomical.rise, None);
+        assert_eq!(times.astronomical.set, None);
+    }
+
+    #[test]
+    fn matches_the_almanac_at_midwinter() {
+        // Sunrise 08:04, solar noon 11:58 and sunset 15:54 GMT in London on 21 December 2024
+        let times = sun_times(LONDON, date(2024, 12, 21));
+        within_minutes(times.daylight.rise, 0, "08:04", 1);
+        within_minutes(Some(times.solar_noon), 0, "11:58", 1);
+        within_minutes(times.daylight.set, 0, "15:54", 1);
+        // Night is nearly 12 hours long, the sun is 18° below the horizon six hours from noon
+        let noon = times.solar_noon;
+        let dawn = noon - times.astronomical.rise.unwrap();
+        let dusk = times.astronomical.set.unwrap() - noon;
+        assert!((dawn.num_minutes() - 360).abs() < 3, "{}", dawn);
+        assert!((dusk.num_minutes() - 360).abs() < 3, "{}", dusk);
+        // Civil twilight 07:25 to 16:34 and nautical 06:42 to 17:16
+        within_minutes(times.civil.rise, 0, "07:25", 2);
+        within_minutes(times.civil.set, 0, "16:34", 2);
+        within_minutes(times.nautical.rise, 0, "06:42", 2);
+        within_minutes(times.nautical.set, 0, "17:16", 2);
+    }
+
+    #[test]
+    fn twilights_nest_around_the_day() {
+        let times = sun_times(DYSERTH, date(2024, 3, 20));
+        let order = [
+            times.astronomical.rise,
+            times.nautical.rise,
+            times.civil.rise,
+            times.daylight.rise,
+            Some(times.solar_noon),
+            times.daylight.set,
+            times.civil.set,
+            times.nautical.set,
+            times.astronomical.set,
+        ];
+        for pair in order.windows(2) {
+            assert!(pair[0].unwrap() < pair[1].unwrap());
+        }
+    }
+
+    #[test]
+    fn handles_the_midnight_sun_and_polar_night() {
+        let tromso = Observer::new(69.6496, 18.9560);
+        let summer = sun_times(tromso, date(2024, 6, 21));
+        assert_eq!(
+            summer.daylight,
+            RiseSet {
+                rise: None,
+                set: None
+            }
+        );
+        let winter = sun_times(tromso, date(2024, 12, 21));
+        assert_eq!(
+            winter.daylight,
+            RiseSet {
+                rise: None,
+                set: None
+            }
+        );
+        // Still some twilight at midday
+        assert!(winter.civil.rise.is_some());
+    }
+
+    #[test]
+    fn works_east_of_greenwich_and_south_of_the_equator() {
+        // Sydney on 1 January 2025: sunrise 05:48, sunset 20:10 AEDT
+        let sydney = Observer::new(-33.8688, 151.2093);
+        let times = sun_times(sydney, date(2025, 1, 1));
+        within_minutes(times.daylight.rise, 11, "05:48", 2);
+        within_minutes(times.daylight.set, 11, "20:10", 2);
+    }
+}
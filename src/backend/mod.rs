@@ -0,0 +1,740 @@
+use crate::client::{
+    LocationForecast, Units, WeathemaCommand, WeatherAPI, WeatherForecastResponse,
+};
+use crate::config::Config;
+use crate::output::csv;
+use crate::rules::severe::{self, SevereAlert};
+use crate::rules::webhook::Webhooks;
+use std::collections::HashSet;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+/// What the backend tells the UI.
+pub(crate) enum WeathemaComponentMessaging {
+    Waiting,
+    /// The forecast, in the units and with the day to show in detail
+    Received(WeatherForecastResponse, Units, usize),
+    Cancelled,
+    Error(String),
+    Status(String),
+    Alerts(Vec<SevereAlert>, Units),
+    /// The dashboard's locations, or `None` when it is closed
+    Dashboard(Option<Vec<LocationForecast>>, Units),
+}
+
+struct BackendState {
+    location: Option<String>,
+    forecast: Option<WeatherForecastResponse>,
+    units: Units,
+    /// The day of the forecast shown in detail, counting from today
+    day: usize,
+    webhooks: Webhooks,
+    /// Alerts the banner has been dismissed for, by location id, date and hazard
+    dismissed: HashSet<(String, String, String)>,
+    /// Locations compared on the dashboard alongside the current one
+    dashboard_locations: Vec<String>,
+    /// The dashboard's forecasts while it is open
+    dashboard: Option<Vec<LocationForecast>>,
+}
+
+pub(crate) async fn poll_backend_service(
+    weather_api: WeatherAPI,
+    tx: UnboundedSender<WeathemaComponentMessaging>,
+    mut rx: UnboundedReceiver<WeathemaCommand>,
+    initial_location: Option<String>,
+    units: Units,
+    config: Config,
+) {
+    let mut state = BackendState {
+        location: None,
+        forecast: None,
+        units,
+        day: 0,
+        webhooks: Webhooks::new(config.rules),
+        dismissed: HashSet::new(),
+        dashboard_locations: config.locations,
+        dashboard: None,
+    };
+
+    let mut next = initial_location.map(WeathemaCommand::Fetch);
+
+    loop {
+        let command = match next.take() {
+            Some(command) => command,
+            None => match rx.recv().await {
+                Some(command) => command,
+                // Every sender has gone, so the UI has shut down
+                None => return,
+            },
+        };
+
+        match command {
+            WeathemaCommand::Fetch(location) => {
+                // Nothing should show or export the last location's forecast from now on
+                if state.location.as_ref() != Some(&location) {
+                    state.forecast = None;
+                    state.day = 0;
+                }
+                state.location = Some(location);
+                next = get_weather(&tx, &mut rx, &weather_api, &mut state).await;
+                if next.is_none() && state.dashboard.is_some() {
+                    next = get_dashboard(&tx, &mut rx, &weather_api, &mut state).await;
+                }
+            }
+            WeathemaCommand::Refresh => {
+                if state.location.is_some() {
+                    next = get_weather(&tx, &mut rx, &weather_api, &mut state).await;
+                }
+                if next.is_none() && state.dashboard.is_some() {
+                    next = get_dashboard(&tx, &mut rx, &weather_api, &mut state).await;
+                }
+            }
+            // Nothing is in flight when we get here
+            WeathemaCommand::Cancel => {}
+            WeathemaCommand::ChangeUnits(units) => {
+                state.units = units;
+                if let Some(forecast) = &state.forecast {
+                    let message =
+                        WeathemaComponentMessaging::Received(forecast.clone(), units, state.day);
+                    if tx.send(message).is_err() || tx.send(alerts(&state)).is_err() {
+                        return;
+                    }
+                }
+                if let Some(dashboard) = &state.dashboard {
+                    let message =
+                        WeathemaComponentMessaging::Dashboard(Some(dashboard.clone()), units);
+                    if tx.send(message).is_err() {
+                        return;
+                    }
+                }
+            }
+            WeathemaCommand::ToggleDashboard => {
+                if state.dashboard.take().is_none() {
+                    next = get_dashboard(&tx, &mut rx, &weather_api, &mut state).await;
+                } else {
+                    let closed = WeathemaComponentMessaging::Dashboard(None, state.units);
+                    if tx.send(closed).is_err() {
+                        return;
+                    }
+                    // Put the current forecast back on the graph
+                    if let Some(forecast) = &state.forecast {
+                        let message = WeathemaComponentMessaging::Received(
+                            forecast.clone(),
+                            state.units,
+                            state.day,
+                        );
+                        if tx.send(message).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            WeathemaCommand::NextDay | WeathemaCommand::PreviousDay => {
+                let Some(forecast) = &state.forecast else {
+                    continue;
+                };
+                let last = forecast.forecasts.len().saturating_sub(1);
+                let day = match command {
+                    WeathemaCommand::NextDay => (state.day + 1).min(last),
+                    _ => state.day.saturating_sub(1),
+                };
+                if day != state.day {
+                    state.day = day;
+                    let message =
+                        WeathemaComponentMessaging::Received(forecast.clone(), state.units, day);
+                    if tx.send(message).is_err() {
+                        return;
+                    }
+                }
+            }
+            WeathemaCommand::Export => {
+                if tx.send(export(&state)).is_err() {
+                    return;
+                }
+            }
+            WeathemaCommand::DismissAlerts => {
+                if tx.send(dismiss_alerts(&mut state)).is_err() {
+                    return;
+                }
+            }
+            WeathemaCommand::Quit => return,
+        }
+    }
+}
+
+/// The severe weather alerts for the current forecast that haven't been dismissed.
+fn current_alerts(state: &BackendState) -> Vec<SevereAlert> {
+    let Some(forecast) = &state.forecast else {
+        return Vec::new();
+    };
+    severe::assess(forecast)
+        .into_iter()
+        .filter(|alert| !state.dismissed.contains(&alert.key()))
+        .collect()
+}
+
+fn alerts(state: &BackendState) -> WeathemaComponentMessaging {
+    WeathemaComponentMessaging::Alerts(current_alerts(state), state.units)
+}
+
+/// Hides the alerts currently shown, new ones still appear after a refresh.
+fn dismiss_alerts(state: &mut BackendState) -> WeathemaComponentMessaging {
+    let shown = current_alerts(state);
+    state
+        .dismissed
+        .extend(shown.iter().map(|alert| alert.key()));
+    WeathemaComponentMessaging::Alerts(Vec::new(), state.units)
+}
+
+/// Exports the current forecast as CSV into the working directory.
+fn export(state: &BackendState) -> WeathemaComponentMessaging {
+    let Some(forecast) = &state.forecast else {
+        return WeathemaComponentMessaging::Status("Nothing to export".to_string());
+    };
+
+    let exported = std::env::current_dir()
+        .map_err(|err| err.into())
+        .and_then(|dir| csv::export_csv(forecast, state.units, &dir));
+    match exported {
+        Ok(paths) => {
+            let names = paths
+                .iter()
+                .filter_map(|path| path.file_name())
+                .map(|name| name.to_string_lossy())
+                .collect::<Vec<_>>();
+            WeathemaComponentMessaging::Status(format!("Exported {}", names.join(", ")))
+        }
+        Err(err) => WeathemaComponentMessaging::Status(format!("Export failed: {}", err)),
+    }
+}
+
+/// Sends the webhooks for any rules a new forecast sets off, reporting failures on the status line.
+fn send_webhooks(
+    tx: &UnboundedSender<WeathemaComponentMessaging>,
+    webhooks: &mut Webhooks,
+    forecast: &WeatherForecastResponse,
+) {
+    for delivery in webhooks.due(forecast) {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let result = match delivery {
+                Ok(delivery) => {
+                    let rule = delivery.rule.clone();
+                    delivery
+                        .send()
+                        .await
+                        .map_err(|err| format!("Webhook for {} failed: {}", rule, err))
+                }
+                Err(err) => Err(err.to_string()),
+            };
+            if let Err(status) = result {
+                let _ = tx.send(WeathemaComponentMessaging::Status(status));
+            }
+        });
+    }
+}
+
+/// Acts on the commands that can be handled while a request is in flight.
+///
+/// Returns any other command, such as a new location or quit, which supersedes the request.
+fn handle_in_flight(
+    command: WeathemaCommand,
+    tx: &UnboundedSender<WeathemaComponentMessaging>,
+    state: &mut BackendState,
+) -> Option<WeathemaCommand> {
+    // A closed channel is noticed once the request finishes
+    match command {
+        WeathemaCommand::ChangeUnits(units) => state.units = units,
+        WeathemaCommand::Export => {
+            let _ = tx.send(export(state));
+        }
+        WeathemaCommand::DismissAlerts => {
+            let _ = tx.send(dismiss_alerts(state));
+        }
+        // Already fetching the latest data
+        WeathemaCommand::Refresh => {}
+        // The day is picked from the forecast once it is in
+        WeathemaCommand::NextDay | WeathemaCommand::PreviousDay => {}
+        command => return Some(command),
+    }
+    None
+}
+
+/// Fetches the current location and every configured one at once for the dashboard.
+///
+/// Like `get_weather` it keeps listening for commands, returning the one that interrupted it.
+async fn get_dashboard(
+    tx: &UnboundedSender<WeathemaComponentMessaging>,
+    rx: &mut UnboundedReceiver<WeathemaCommand>,
+    weather_api: &WeatherAPI,
+    state: &mut BackendState,
+) -> Option<WeathemaCommand> {
+    let mut locations: Vec<String> = state.location.iter().cloned().collect();
+    for location in &state.dashboard_locations {
+        if !locations
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(location))
+        {
+            locations.push(location.clone());
+        }
+    }
+
+    // Open until toggled again, so a toggle while fetching closes it
+    let opening = state.dashboard.is_none();
+    state.dashboard.get_or_insert_with(Vec::new);
+
+    let status = format!("Fetching {} locations", locations.len());
+    if tx.send(WeathemaComponentMessaging::Status(status)).is_err() {
+        return Some(WeathemaCommand::Quit);
+    }
+
+    let forecasts = {
+        let request = futures::future::join_all(
+            locations
+                .iter()
+                .map(|location| weather_api.get_weather(location)),
+        );
+        tokio::pin!(request);
+
+        loop {
+            tokio::select! {
+                forecasts = &mut request => break forecasts,
+                command = rx.recv() => match command {
+                    Some(WeathemaCommand::Cancel) => {
+                        if opening {
+                            state.dashboard = None;
+                        }
+                        let status = WeathemaComponentMessaging::Status("Cancelled".to_string());
+                        return tx.send(status).is_err().then_some(WeathemaCommand::Quit);
+                    }
+                    Some(command) => {
+                        if let Some(command) = handle_in_flight(command, tx, state) {
+                            return Some(command);
+                        }
+                    }
+                    None => return Some(WeathemaCommand::Quit),
+                },
+            }
+        }
+    };
+
+    let dashboard: Vec<_> = locations
+        .into_iter()
+        .zip(forecasts)
+        .map(|(location, forecast)| LocationForecast {
+            location,
+            forecast: forecast.map_err(|err| err.to_string()),
+        })
+        .collect();
+    state.dashboard = Some(dashboard.clone());
+
+    let status = if dashboard.len() > 1 {
+        String::new()
+    } else {
+        "Add locations to the config file to compare them".to_string()
+    };
+    let message = WeathemaComponentMessaging::Dashboard(Some(dashboard), state.units);
+    if tx.send(WeathemaComponentMessaging::Status(status)).is_err() || tx.send(message).is_err() {
+        return Some(WeathemaCommand::Quit);
+    }
+    None
+}
+
+/// Fetches the weather for the current location while still listening for commands.
+///
+/// Returns the command that interrupted the request, if any, so that the caller can act on it.
+/// Toggling the dashboard waits for the forecast, so that the dashboard includes the new location.
+async fn get_weather(
+    tx: &UnboundedSender<WeathemaComponentMessaging>,
+    rx: &mut UnboundedReceiver<WeathemaCommand>,
+    weather_api: &WeatherAPI,
+    state: &mut BackendState,
+) -> Option<WeathemaCommand> {
+    let location = state.location.clone()?;
+
+    if tx.send(WeathemaComponentMessaging::Waiting).is_err() {
+        return Some(WeathemaCommand::Quit);
+    }
+
+    let request = weather_api.get_weather(&location);
+    tokio::pin!(request);
+    let mut toggle_dashboard = false;
+
+    let message = loop {
+        tokio::select! {
+            result = &mut request => {
+                break match result {
+                    Ok(forecast) => {
+                        send_webhooks(tx, &mut state.webhooks, &forecast);
+                        // A refreshed forecast may have a day less
+                        state.day = state.day.min(forecast.forecasts.len().saturating_sub(1));
+                        state.forecast = Some(forecast.clone());
+                        WeathemaComponentMessaging::Received(forecast, state.units, state.day)
+                    }
+                    Err(err) => WeathemaComponentMessaging::Error(err.to_string()),
+                };
+            }
+            command = rx.recv() => match command {
+                Some(WeathemaCommand::Cancel) => break WeathemaComponentMessaging::Cancelled,
+                Some(WeathemaCommand::ToggleDashboard) => toggle_dashboard = !toggle_dashboard,
+                Some(command) => {
+                    if let Some(command) = handle_in_flight(command, tx, state) {
+                        return Some(command);
+                    }
+                }
+                None => return Some(WeathemaCommand::Quit),
+            },
+        }
+    };
+
+    // The banner follows whatever the main panel is showing
+    let banner = match message {
+        WeathemaComponentMessaging::Received(..) => Some(alerts(state)),
+        WeathemaComponentMessaging::Error(_) => {
+            Some(WeathemaComponentMessaging::Alerts(Vec::new(), state.units))
+        }
+        _ => None,
+    };
+    if tx.send(message).is_err() {
+        return Some(WeathemaCommand::Quit);
+    }
+    if let Some(banner) = banner {
+        if tx.send(banner).is_err() {
+            return Some(WeathemaCommand::Quit);
+        }
+    }
+    toggle_dashboard.then_some(WeathemaCommand::ToggleDashboard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::upstream;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio::task::JoinHandle;
+
+    /// The backend running against the stand-in upstream, driven as the UI would.
+    struct Backend {
+        commands: UnboundedSender<WeathemaCommand>,
+        messages: UnboundedReceiver<WeathemaComponentMessaging>,
+        task: JoinHandle<()>,
+    }
+
+    impl Backend {
+        async fn start(initial_location: Option<&str>) -> Self {
+            Self::with_dashboard(initial_location, &[]).await
+        }
+
+        /// Also compares `locations` on the dashboard.
+        async fn with_dashboard(initial_location: Option<&str>, locations: &[&str]) -> Self {
+            let (weather_api, _) = upstream::start().await;
+            let (tx, messages) = mpsc::unbounded_channel();
+            let (commands, rx) = mpsc::unbounded_channel();
+            let task = tokio::spawn(poll_backend_service(
+                weather_api,
+                tx,
+                rx,
+                initial_location.map(str::to_string),
+                Units::Metric,
+                Config {
+                    locations: locations
+                        .iter()
+                        .map(|location| location.to_string())
+                        .collect(),
+                    ..Config::default()
+                },
+            ));
+            Self {
+                commands,
+                messages,
+                task,
+            }
+        }
+
+        fn send(&self, command: WeathemaCommand) {
+            self.commands.send(command).unwrap();
+        }
+
+        /// The next message for the UI, summed up by `describe`.
+        async fn next(&mut self) -> String {
+            let message = tokio::time::timeout(Duration::from_secs(5), self.messages.recv())
+                .await
+                .expect("no message from the backend")
+                .expect("the backend has stopped");
+            describe(&message)
+        }
+
+        /// Checks nothing more arrives, even once a superseded request would have finished.
+        async fn assert_quiet(&mut self) {
+            tokio::time::sleep(upstream::FORECAST_DELAY * 2).await;
+            if let Ok(message) = self.messages.try_recv() {
+                panic!("unexpected {}", describe(&message));
+            }
+        }
+
+        async fn stopped(self) {
+            tokio::time::timeout(Duration::from_secs(5), self.task)
+                .await
+                .expect("the backend is still running")
+                .unwrap();
+        }
+    }
+
+    fn describe(message: &WeathemaComponentMessaging) -> String {
+        match message {
+            WeathemaComponentMessaging::Waiting => "Waiting".to_string(),
+            WeathemaComponentMessaging::Received(forecast, units, 0) => {
+                format!("Received {} {:?}", forecast.location.name, units)
+            }
+            WeathemaComponentMessaging::Received(forecast, units, day) => {
+                format!(
+                    "Received {} {:?} day {}",
+                    forecast.location.name, units, day
+                )
+            }
+            WeathemaComponentMessaging::Cancelled => "Cancelled".to_string(),
+            WeathemaComponentMessaging::Error(err) => format!("Error {}", err),
+            WeathemaComponentMessaging::Status(status) => format!("Status {}", status),
+            WeathemaComponentMessaging::Alerts(alerts, _) => format!("Alerts {}", alerts.len()),
+            WeathemaComponentMessaging::Dashboard(Some(locations), _) => {
+                let names: Vec<_> = locations
+                    .iter()
+                    .map(|location| match &location.forecast {
+                        Ok(_) => location.location.clone(),
+                        Err(_) => format!("{} failed", location.location),
+                    })
+                    .collect();
+                format!("Dashboard {}", names.join(", "))
+            }
+            WeathemaComponentMessaging::Dashboard(None, _) => "Dashboard closed".to_string(),
+        }
+    }
+
+    /// The banner for the test forecast.
+    fn banner() -> String {
+        format!(
+            "Alerts {}",
+            severe::assess(&crate::client::test_forecast()).len()
+        )
+    }
+
+    #[tokio::test]
+    async fn fetches_the_initial_location() {
+        let mut backend = Backend::start(Some("Dyserth")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+        assert_eq!(backend.next().await, banner());
+    }
+
+    #[tokio::test]
+    async fn reports_locations_that_cannot_be_found() {
+        let mut backend = Backend::start(Some("Nowhere")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Error No results found");
+        // The banner is cleared along with the forecast
+        assert_eq!(backend.next().await, "Alerts 0");
+    }
+
+    #[tokio::test]
+    async fn cancels_a_fetch() {
+        let mut backend = Backend::start(Some("Dyserth")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        backend.send(WeathemaCommand::Cancel);
+        assert_eq!(backend.next().await, "Cancelled");
+        backend.assert_quiet().await;
+    }
+
+    #[tokio::test]
+    async fn a_new_location_supersedes_a_fetch() {
+        let mut backend = Backend::start(Some("Dyserth")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        backend.send(WeathemaCommand::Fetch("Nowhere".to_string()));
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Error No results found");
+        assert_eq!(backend.next().await, "Alerts 0");
+        // Dyserth's forecast never arrives
+        backend.assert_quiet().await;
+    }
+
+    #[tokio::test]
+    async fn changes_units_while_fetching() {
+        let mut backend = Backend::start(Some("Dyserth")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        backend.send(WeathemaCommand::ChangeUnits(Units::Imperial));
+        // Also a refresh, which the fetch already covers
+        backend.send(WeathemaCommand::Refresh);
+        assert_eq!(backend.next().await, "Received Dyserth Imperial");
+        assert_eq!(backend.next().await, banner());
+        backend.assert_quiet().await;
+    }
+
+    #[tokio::test]
+    async fn redraws_the_forecast_in_new_units() {
+        let mut backend = Backend::start(Some("Dyserth")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+        assert_eq!(backend.next().await, banner());
+
+        backend.send(WeathemaCommand::ChangeUnits(Units::Imperial));
+        assert_eq!(backend.next().await, "Received Dyserth Imperial");
+        assert_eq!(backend.next().await, banner());
+    }
+
+    #[tokio::test]
+    async fn moves_the_day_shown_along_the_forecast() {
+        let mut backend = Backend::start(Some("Dyserth")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        // Nothing to move along yet, and the fetch carries on
+        backend.send(WeathemaCommand::NextDay);
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+        assert_eq!(backend.next().await, banner());
+
+        backend.send(WeathemaCommand::PreviousDay);
+        backend.send(WeathemaCommand::NextDay);
+        assert_eq!(backend.next().await, "Received Dyserth Metric day 1");
+        backend.send(WeathemaCommand::NextDay);
+        assert_eq!(backend.next().await, "Received Dyserth Metric day 2");
+
+        // Kept through a refresh and a change of units
+        backend.send(WeathemaCommand::Refresh);
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Received Dyserth Metric day 2");
+        assert_eq!(backend.next().await, banner());
+        backend.send(WeathemaCommand::ChangeUnits(Units::Imperial));
+        assert_eq!(backend.next().await, "Received Dyserth Imperial day 2");
+        assert_eq!(backend.next().await, banner());
+
+        for _ in 0..20 {
+            backend.send(WeathemaCommand::NextDay);
+        }
+        for day in 3..14 {
+            assert_eq!(
+                backend.next().await,
+                format!("Received Dyserth Imperial day {}", day)
+            );
+        }
+        backend.assert_quiet().await;
+    }
+
+    #[tokio::test]
+    async fn shows_today_for_a_new_location() {
+        let mut backend = Backend::start(Some("Dyserth")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+        assert_eq!(backend.next().await, banner());
+        backend.send(WeathemaCommand::NextDay);
+        assert_eq!(backend.next().await, "Received Dyserth Metric day 1");
+
+        backend.send(WeathemaCommand::Fetch(upstream::LOCATION_ID.to_string()));
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+    }
+
+    #[tokio::test]
+    async fn refreshes_the_current_location() {
+        let mut backend = Backend::start(Some("Dyserth")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+        assert_eq!(backend.next().await, banner());
+
+        backend.send(WeathemaCommand::Refresh);
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+    }
+
+    #[tokio::test]
+    async fn ignores_a_refresh_with_no_location() {
+        let mut backend = Backend::start(None).await;
+        backend.send(WeathemaCommand::Refresh);
+        backend.send(WeathemaCommand::Cancel);
+        backend.assert_quiet().await;
+    }
+
+    #[tokio::test]
+    async fn quits_mid_fetch() {
+        let mut backend = Backend::start(Some("Dyserth")).await;
+        assert_eq!(backend.next().await, "Waiting");
+        backend.send(WeathemaCommand::Quit);
+        backend.stopped().await;
+    }
+
+    #[tokio::test]
+    async fn stops_once_the_ui_has_gone() {
+        let backend = Backend::start(None).await;
+        let Backend { commands, task, .. } = backend;
+        drop(commands);
+        tokio::time::timeout(Duration::from_secs(5), task)
+            .await
+            .expect("the backend is still running")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn opens_the_dashboard_without_repeating_the_current_location() {
+        let mut backend = Backend::with_dashboard(Some("Dyserth"), &["dyserth", "Nowhere"]).await;
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+        assert_eq!(backend.next().await, banner());
+
+        backend.send(WeathemaCommand::ToggleDashboard);
+        assert_eq!(backend.next().await, "Status Fetching 2 locations");
+        assert_eq!(backend.next().await, "Status ");
+        // Locations that can't be fetched still get a card saying why
+        assert_eq!(backend.next().await, "Dashboard Dyserth, Nowhere failed");
+
+        backend.send(WeathemaCommand::ToggleDashboard);
+        assert_eq!(backend.next().await, "Dashboard closed");
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+    }
+
+    #[tokio::test]
+    async fn cancels_opening_the_dashboard() {
+        let mut backend = Backend::with_dashboard(None, &["Dyserth"]).await;
+        backend.send(WeathemaCommand::ToggleDashboard);
+        assert_eq!(backend.next().await, "Status Fetching 1 locations");
+        backend.send(WeathemaCommand::Cancel);
+        assert_eq!(backend.next().await, "Status Cancelled");
+        backend.assert_quiet().await;
+
+        // Still closed, so toggling opens it
+        backend.send(WeathemaCommand::ToggleDashboard);
+        assert_eq!(backend.next().await, "Status Fetching 1 locations");
+    }
+
+    #[tokio::test]
+    async fn finishes_a_fetch_before_opening_the_dashboard() {
+        let mut backend = Backend::with_dashboard(Some("Dyserth"), &["Nowhere"]).await;
+        assert_eq!(backend.next().await, "Waiting");
+        backend.send(WeathemaCommand::ToggleDashboard);
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+        assert_eq!(backend.next().await, banner());
+        assert_eq!(backend.next().await, "Status Fetching 2 locations");
+        assert_eq!(backend.next().await, "Status ");
+        assert_eq!(backend.next().await, "Dashboard Dyserth, Nowhere failed");
+    }
+
+    #[tokio::test]
+    async fn forgets_the_forecast_of_the_last_location() {
+        let mut backend = Backend::with_dashboard(Some("Dyserth"), &["Dyserth"]).await;
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Received Dyserth Metric");
+        assert_eq!(backend.next().await, banner());
+
+        backend.send(WeathemaCommand::Fetch("Nowhere".to_string()));
+        assert_eq!(backend.next().await, "Waiting");
+        assert_eq!(backend.next().await, "Error No results found");
+        assert_eq!(backend.next().await, "Alerts 0");
+
+        backend.send(WeathemaCommand::ToggleDashboard);
+        assert_eq!(backend.next().await, "Status Fetching 2 locations");
+        assert_eq!(backend.next().await, "Status ");
+        assert_eq!(backend.next().await, "Dashboard Nowhere failed, Dyserth");
+
+        // Closing it doesn't bring back Dyserth's forecast as if it were Nowhere's
+        backend.send(WeathemaCommand::ToggleDashboard);
+        assert_eq!(backend.next().await, "Dashboard closed");
+        backend.send(WeathemaCommand::Export);
+        assert_eq!(backend.next().await, "Status Nothing to export");
+        backend.assert_quiet().await;
+    }
+}
@@ -0,0 +1,274 @@
+use crate::client::{ForecastCache, Units, WeatherAPI};
+use crate::config::Config;
+use crate::output::text::{self, TextOptions};
+use crate::output::{bar, csv, ics, json};
+use crate::rules::notify;
+use crate::{mqtt, server};
+use clap::{Args, Subcommand, ValueEnum};
+use std::io::IsTerminal;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// Commands that print to stdout and exit instead of starting the TUI.
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Print today's weather
+    Now {
+        /// Place name, `lat,lon` coordinates, UK postcode or BBC location id
+        location: String,
+        /// Leave out the ASCII art
+        #[arg(long)]
+        no_art: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Print the daily forecast as a table
+    Forecast {
+        /// Place name, `lat,lon` coordinates, UK postcode or BBC location id
+        location: String,
+        /// Number of days to show
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=14))]
+        days: u8,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Write the forecast to files
+    Export {
+        /// Place name, `lat,lon` coordinates, UK postcode or BBC location id
+        location: String,
+        /// Directory to write the files into
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+    },
+    /// Print a one line summary for status bars such as tmux, waybar and polybar
+    Bar {
+        /// Place name, `lat,lon` coordinates, UK postcode or BBC location id
+        location: String,
+        /// Placeholders: {icon} {text} {temp} {min} {max} {unit} {wind_dir} {wind_speed}
+        /// {speed_unit} {rain} {location}
+        #[arg(long, default_value = bar::DEFAULT_FORMAT)]
+        format: String,
+        /// Print waybar JSON with a tooltip and a CSS class for the weather type
+        #[arg(long)]
+        waybar: bool,
+        /// Seconds a cached forecast is used before fetching a new one
+        #[arg(long, default_value_t = 900)]
+        max_age: u64,
+    },
+    /// Serve forecasts as a JSON API and Prometheus metrics over HTTP
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: SocketAddr,
+        /// Location to export metrics for, may be repeated, defaults to those in the config file
+        #[arg(long = "location")]
+        locations: Vec<String>,
+        /// Seconds a cached forecast is used before fetching a new one
+        #[arg(long, default_value_t = 900)]
+        max_age: u64,
+    },
+    /// Publish the weather to the MQTT broker in the config file, with Home Assistant discovery
+    Mqtt {
+        /// Location to publish, may be repeated, defaults to those in the config file
+        #[arg(long = "location")]
+        locations: Vec<String>,
+        /// Publish once and exit instead of refreshing every `interval` seconds
+        #[arg(long)]
+        once: bool,
+    },
+    /// Send desktop notifications for the rules in the config file with `notify = true`
+    Watch {
+        /// Location to watch, may be repeated, defaults to those in the config file
+        #[arg(long = "location")]
+        locations: Vec<String>,
+        /// Seconds between checks
+        #[arg(long, default_value_t = 600)]
+        interval: u64,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum ExportFormat {
+    /// `<location>-daily.csv` and `<location>-hourly.csv`
+    Csv,
+    /// `<location>.ics` with an all-day event per day
+    Ics,
+}
+
+#[derive(Args)]
+pub(crate) struct OutputArgs {
+    /// Output format, json and ndjson are described in docs/json-output.md
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    /// When to use ANSI colours in text output
+    #[arg(long, value_enum, default_value_t = ColourChoice::Auto)]
+    colour: ColourChoice,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum OutputFormat {
+    Text,
+    Json,
+    Ndjson,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum ColourChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColourChoice {
+    fn enabled(self) -> bool {
+        match self {
+            ColourChoice::Auto => std::io::stdout().is_terminal(),
+            ColourChoice::Always => true,
+            ColourChoice::Never => false,
+        }
+    }
+}
+
+pub(crate) async fn run(command: Command, units: Units, config: Option<&Path>) -> ExitCode {
+    let result = match Config::load(config) {
+        Ok(config) => run_command(command, units, config).await,
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run_command(
+    command: Command,
+    units: Units,
+    config: Config,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let weather_api = WeatherAPI::new();
+
+    match command {
+        Command::Now {
+            location,
+            no_art,
+            output,
+        } => {
+            let forecast = weather_api.get_weather(&location).await?;
+            let rendered = match output.format {
+                OutputFormat::Text => {
+                    let options = TextOptions {
+                        units,
+                        colour: output.colour.enabled(),
+                        art: !no_art,
+                    };
+                    text::render_now(&forecast, &options)
+                }
+                OutputFormat::Json => json::render_json(&forecast, 1)?,
+                OutputFormat::Ndjson => json::render_ndjson(&forecast, 1)?,
+            };
+            print!("{}", rendered);
+        }
+        Command::Forecast {
+            location,
+            days,
+            output,
+        } => {
+            let forecast = weather_api.get_weather(&location).await?;
+            let days = days as usize;
+            let rendered = match output.format {
+                OutputFormat::Text => {
+                    let options = TextOptions {
+                        units,
+                        colour: output.colour.enabled(),
+                        art: false,
+                    };
+                    text::render_forecast(&forecast, days, &options)
+                }
+                OutputFormat::Json => json::render_json(&forecast, days)?,
+                OutputFormat::Ndjson => json::render_ndjson(&forecast, days)?,
+            };
+            print!("{}", rendered);
+        }
+        Command::Export {
+            location,
+            dir,
+            format,
+        } => {
+            let forecast = weather_api.get_weather(&location).await?;
+            let paths = match format {
+                ExportFormat::Csv => csv::export_csv(&forecast, units, &dir)?,
+                ExportFormat::Ics => vec![ics::export_ics(&forecast, units, &dir)?],
+            };
+            for path in paths {
+                println!("{}", path.display());
+            }
+        }
+        Command::Bar {
+            location,
+            format,
+            waybar,
+            max_age,
+        } => {
+            let weather_api = with_shared_cache(weather_api, max_age);
+            let forecast = weather_api.get_weather(&location).await?;
+            if waybar {
+                println!(
+                    "{}",
+                    bar::render_waybar(&forecast, &format, units, chrono::Utc::now())?
+                );
+            } else {
+                println!(
+                    "{}",
+                    bar::render_line(&forecast, &format, units, chrono::Utc::now())
+                );
+            }
+        }
+        Command::Serve {
+            bind,
+            locations,
+            max_age,
+        } => {
+            let locations = if locations.is_empty() {
+                config.locations
+            } else {
+                locations
+            };
+            server::serve(bind, with_shared_cache(weather_api, max_age), locations).await?;
+        }
+        Command::Mqtt { locations, once } => {
+            let locations = if locations.is_empty() {
+                config.locations
+            } else {
+                locations
+            };
+            mqtt::run(weather_api, locations, config.mqtt, once).await?;
+        }
+        Command::Watch {
+            locations,
+            interval,
+        } => {
+            let locations = if locations.is_empty() {
+                config.locations
+            } else {
+                locations
+            };
+            let interval = Duration::from_secs(interval.max(1));
+            notify::watch(weather_api, locations, config.rules, interval).await?;
+        }
+    }
+    Ok(())
+}
+
+fn with_shared_cache(weather_api: WeatherAPI, max_age: u64) -> WeatherAPI {
+    match ForecastCache::default_dir() {
+        Some(dir) => weather_api.with_cache(ForecastCache::new(dir, Duration::from_secs(max_age))),
+        None => weather_api,
+    }
+}
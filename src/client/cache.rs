@@ -0,0 +1,174 @@
+use super::query::normalise_location;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// An on-disk cache of forecast responses shared by every weathema process for the user.
+///
+/// Entries hold the raw provider response so they can be decoded exactly as a fresh one would be.
+pub(crate) struct ForecastCache {
+    dir: PathBuf,
+    max_age: Duration,
+}
+
+#[derive(Deserialize, Serialize)]
+struct CacheEntry {
+    fetched_at: u64,
+    body: String,
+}
+
+impl ForecastCache {
+    pub fn new(dir: PathBuf, max_age: Duration) -> Self {
+        Self { dir, max_age }
+    }
+
+    /// `$XDG_CACHE_HOME/weathema`, falling back to `~/.cache/weathema`.
+    pub fn default_dir() -> Option<PathBuf> {
+        let base = match std::env::var_os("XDG_CACHE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+        };
+        Some(base.join("weathema"))
+    }
+
+    /// Returns the cached body for `location` if it is younger than the maximum age.
+    pub fn get(&self, location: &str) -> Option<String> {
+        let contents = std::fs::read_to_string(self.path(location)?).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&contents).ok()?;
+
+        (!self.is_expired(&entry)).then_some(entry.body)
+    }
+
+    pub fn put(
+        &self,
+        location: &str,
+        body: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(path) = self.path(location) else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(&self.dir)?;
+
+        let entry = CacheEntry {
+            fetched_at: now(),
+            body: body.to_string(),
+        };
+
+        // Write then rename so that concurrent readers never see a partial entry
+        let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&temporary, serde_json::to_string(&entry)?)?;
+        std::fs::rename(&temporary, &path)?;
+
+        self.sweep();
+        Ok(())
+    }
+
+    /// Deletes expired entries, so locations that are no longer looked up don't pile up on disk.
+    fn sweep(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let expired = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| serde_json::from_str::<CacheEntry>(&contents).ok())
+                .is_none_or(|entry| self.is_expired(&entry));
+            if expired {
+                // Another process may have swept it first
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        now().saturating_sub(entry.fetched_at) > self.max_age.as_secs()
+    }
+
+    fn path(&self, location: &str) -> Option<PathBuf> {
+        let key = normalise_location(location).ok()?.to_lowercase();
+        Some(
+            self.dir
+                .join(format!("{:016x}.json", fnv1a(key.as_bytes()))),
+        )
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// FNV-1a, used for file names as it is stable across Rust releases unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(name: &str, max_age: Duration) -> ForecastCache {
+        let dir =
+            std::env::temp_dir().join(format!("weathema-cache-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        ForecastCache::new(dir, max_age)
+    }
+
+    #[test]
+    fn returns_fresh_entries() {
+        let cache = cache("fresh", Duration::from_secs(60));
+        assert_eq!(cache.get("Dyserth"), None);
+
+        cache.put("Dyserth", "{}").unwrap();
+        assert_eq!(cache.get("Dyserth").as_deref(), Some("{}"));
+        assert_eq!(cache.get("  dyserth ").as_deref(), Some("{}"));
+        assert_eq!(cache.get("Rhyl"), None);
+    }
+
+    #[test]
+    fn ignores_expired_entries() {
+        let cache = cache("expired", Duration::from_secs(60));
+        let entry = CacheEntry {
+            fetched_at: now() - 120,
+            body: "{}".to_string(),
+        };
+        std::fs::create_dir_all(&cache.dir).unwrap();
+        std::fs::write(
+            cache.path("Dyserth").unwrap(),
+            serde_json::to_string(&entry).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(cache.get("Dyserth"), None);
+    }
+
+    #[test]
+    fn sweeps_expired_entries() {
+        let cache = cache("sweep", Duration::from_secs(60));
+        cache.put("Rhyl", "{}").unwrap();
+        let expired = CacheEntry {
+            fetched_at: now() - 120,
+            body: "{}".to_string(),
+        };
+        std::fs::write(
+            cache.path("Dyserth").unwrap(),
+            serde_json::to_string(&expired).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(cache.dir.join("notes.txt"), "kept").unwrap();
+
+        cache.put("Prestatyn", "{}").unwrap();
+
+        assert!(!cache.path("Dyserth").unwrap().exists());
+        assert_eq!(cache.get("Rhyl").as_deref(), Some("{}"));
+        assert_eq!(cache.get("Prestatyn").as_deref(), Some("{}"));
+        assert!(cache.dir.join("notes.txt").exists());
+    }
+}
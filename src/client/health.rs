@@ -0,0 +1,138 @@
+use crate::client::WeatherSummaryReport;
+
+/// How much of a health risk an index is, as the BBC bands them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Band {
+    Low,
+    Moderate,
+    High,
+    VeryHigh,
+    Extreme,
+}
+
+impl Band {
+    /// Parses a band such as `MODERATE` or `VERY_HIGH`, ignoring case and separators.
+    pub fn parse(band: &str) -> Option<Self> {
+        let band = band.trim().to_ascii_uppercase().replace([' ', '-'], "_");
+        match band.as_str() {
+            "LOW" => Some(Band::Low),
+            "MODERATE" => Some(Band::Moderate),
+            "HIGH" => Some(Band::High),
+            "VERY_HIGH" => Some(Band::VeryHigh),
+            "EXTREME" => Some(Band::Extreme),
+            _ => None,
+        }
+    }
+
+    pub fn colour(self) -> &'static str {
+        match self {
+            Band::Low => "green",
+            Band::Moderate => "yellow",
+            Band::High => "red",
+            Band::VeryHigh | Band::Extreme => "magenta",
+        }
+    }
+}
+
+/// One of the daily UV, pollen and air pollution indices.
+#[derive(Debug, PartialEq)]
+pub(crate) struct HealthIndex<'a> {
+    pub name: &'static str,
+    pub value: Option<f64>,
+    pub band: Option<Band>,
+    /// The band as the provider words it, e.g. `Moderate`
+    pub text: Option<&'a str>,
+}
+
+impl<'a> HealthIndex<'a> {
+    /// The index and its band, such as `5 Moderate`.
+    pub fn describe(&self) -> String {
+        match (self.value, self.text) {
+            (Some(value), Some(text)) => format!("{} {}", value, text),
+            (Some(value), None) => value.to_string(),
+            (None, _) => "Not forecast".to_string(),
+        }
+    }
+
+    fn new(
+        name: &'static str,
+        value: Option<f64>,
+        band: &Option<String>,
+        text: &'a Option<String>,
+    ) -> Self {
+        Self {
+            name,
+            value,
+            band: band.as_deref().and_then(Band::parse),
+            text: text.as_deref(),
+        }
+    }
+}
+
+impl WeatherSummaryReport {
+    pub fn health_indices(&self) -> [HealthIndex<'_>; 3] {
+        [
+            HealthIndex::new(
+                "UV",
+                self.uv_index,
+                &self.uv_index_band,
+                &self.uv_index_text,
+            ),
+            HealthIndex::new(
+                "Pollen",
+                self.pollen_index,
+                &self.pollen_index_band,
+                &self.pollen_index_text,
+            ),
+            HealthIndex::new(
+                "Pollution",
+                self.pollution_index,
+                &self.pollution_index_band,
+                &self.pollution_index_text,
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+
+    #[test]
+    fn parses_bands() {
+        assert_eq!(Band::parse("LOW"), Some(Band::Low));
+        assert_eq!(Band::parse("Moderate"), Some(Band::Moderate));
+        assert_eq!(Band::parse("VERY_HIGH"), Some(Band::VeryHigh));
+        assert_eq!(Band::parse("very high"), Some(Band::VeryHigh));
+        assert_eq!(Band::parse(" extreme "), Some(Band::Extreme));
+        assert_eq!(Band::parse(""), None);
+    }
+
+    #[test]
+    fn reads_the_days_indices() {
+        let forecast = test_forecast();
+        let [uv, pollen, pollution] = forecast.forecasts[0].summary.report.health_indices();
+        assert_eq!(
+            uv,
+            HealthIndex {
+                name: "UV",
+                value: Some(5.0),
+                band: Some(Band::Moderate),
+                text: Some("Moderate"),
+            }
+        );
+        assert_eq!(pollen.describe(), "4 Moderate");
+        assert_eq!(pollution.band, Some(Band::Low));
+        assert_eq!(pollution.describe(), "2 Low");
+    }
+
+    #[test]
+    fn later_days_leave_some_out() {
+        let forecast = test_forecast();
+        let [uv, pollen, pollution] = forecast.forecasts[5].summary.report.health_indices();
+        assert_eq!(uv.describe(), "3 Moderate");
+        assert_eq!(pollen.describe(), "Not forecast");
+        assert_eq!(pollution.band, None);
+    }
+}
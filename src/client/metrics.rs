@@ -0,0 +1,113 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds in seconds of the request latency histogram buckets.
+pub(crate) const LATENCY_BUCKETS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Endpoint {
+    Locator,
+    Forecast,
+}
+
+impl Endpoint {
+    pub fn name(self) -> &'static str {
+        match self {
+            Endpoint::Locator => "locator",
+            Endpoint::Forecast => "forecast",
+        }
+    }
+}
+
+#[derive(Default)]
+struct EndpointMetrics {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    duration_micros: AtomicU64,
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+}
+
+/// A point in time copy of the counters for one endpoint.
+pub(crate) struct EndpointSnapshot {
+    pub endpoint: Endpoint,
+    pub requests: u64,
+    pub errors: u64,
+    pub duration_seconds: f64,
+    /// Cumulative counts for each of `LATENCY_BUCKETS`
+    pub buckets: [u64; LATENCY_BUCKETS.len()],
+}
+
+/// Counts the requests `WeatherAPI` makes to the upstream services.
+#[derive(Default)]
+pub(crate) struct UpstreamMetrics {
+    locator: EndpointMetrics,
+    forecast: EndpointMetrics,
+}
+
+impl UpstreamMetrics {
+    fn endpoint(&self, endpoint: Endpoint) -> &EndpointMetrics {
+        match endpoint {
+            Endpoint::Locator => &self.locator,
+            Endpoint::Forecast => &self.forecast,
+        }
+    }
+
+    pub fn record(&self, endpoint: Endpoint, duration: Duration, success: bool) {
+        let metrics = self.endpoint(endpoint);
+        metrics.requests.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            metrics.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        metrics
+            .duration_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+
+        let seconds = duration.as_secs_f64();
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&metrics.buckets) {
+            if seconds <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<EndpointSnapshot> {
+        [Endpoint::Locator, Endpoint::Forecast]
+            .into_iter()
+            .map(|endpoint| {
+                let metrics = self.endpoint(endpoint);
+                EndpointSnapshot {
+                    endpoint,
+                    requests: metrics.requests.load(Ordering::Relaxed),
+                    errors: metrics.errors.load(Ordering::Relaxed),
+                    duration_seconds: metrics.duration_micros.load(Ordering::Relaxed) as f64
+                        / 1_000_000.0,
+                    buckets: std::array::from_fn(|index| {
+                        metrics.buckets[index].load(Ordering::Relaxed)
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_requests() {
+        let metrics = UpstreamMetrics::default();
+        metrics.record(Endpoint::Forecast, Duration::from_millis(200), true);
+        metrics.record(Endpoint::Forecast, Duration::from_secs(3), false);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot[0].endpoint, Endpoint::Locator);
+        assert_eq!(snapshot[0].requests, 0);
+
+        let forecast = &snapshot[1];
+        assert_eq!(forecast.requests, 2);
+        assert_eq!(forecast.errors, 1);
+        assert!((forecast.duration_seconds - 3.2).abs() < 1e-9);
+        assert_eq!(forecast.buckets, [0, 0, 1, 1, 1, 1, 2, 2]);
+    }
+}
@@ -1,75 +1,373 @@
+mod cache;
+pub(crate) mod health;
+mod metrics;
+pub(crate) mod pressure;
+mod query;
+pub(crate) mod sun;
+#[cfg(test)]
+pub(crate) mod upstream;
+pub(crate) mod visibility;
+pub(crate) mod wind;
+
+use crate::meteo;
+pub(crate) use cache::ForecastCache;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use metrics::Endpoint;
+pub(crate) use metrics::{UpstreamMetrics, LATENCY_BUCKETS};
+pub(crate) use query::normalise_location;
+use query::LocationQuery;
 use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Instant;
+pub(crate) use wind::WindDirection;
 
-pub struct WeatherAPI;
+const FORECAST_ENDPOINT: &str = "https://weather-broker-cdn.api.bbci.co.uk/en/forecast/aggregated";
+
+pub struct WeatherAPI {
+    client: reqwest::Client,
+    locator_endpoint: String,
+    forecast_endpoint: String,
+    cache: Option<ForecastCache>,
+    metrics: Arc<UpstreamMetrics>,
+}
 
 impl WeatherAPI {
     pub(crate) fn new() -> Self {
-        Self
+        Self {
+            client: reqwest::Client::new(),
+            locator_endpoint: query::LOCATOR_ENDPOINT.to_string(),
+            forecast_endpoint: FORECAST_ENDPOINT.to_string(),
+            cache: None,
+            metrics: Arc::default(),
+        }
+    }
+
+    /// Request counts, errors and latencies for the upstream services.
+    pub(crate) fn metrics(&self) -> &UpstreamMetrics {
+        &self.metrics
+    }
+
+    /// Serves forecasts from `cache` while they are fresh and stores new ones in it.
+    pub(crate) fn with_cache(mut self, cache: ForecastCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Points the client at different locator and forecast services, e.g. a local stand-in.
+    #[cfg(test)]
+    pub(crate) fn with_endpoints(mut self, locator: &str, forecast: &str) -> Self {
+        self.locator_endpoint = locator.to_string();
+        self.forecast_endpoint = forecast.trim_end_matches('/').to_string();
+        self
     }
 
     pub async fn get_weather(
         &self,
         location: &str,
-    ) -> Result<WeatherForecastResponse, Box<dyn std::error::Error>> {
-        let id = self.get_location_id(location).await?;
-        let response = self.get_weather_data(id).await?;
+    ) -> Result<WeatherForecastResponse, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(body) = self.cache.as_ref().and_then(|cache| cache.get(location)) {
+            if let Ok(response) = serde_json::from_str(&body) {
+                return Ok(response);
+            }
+        }
+
+        let body = match LocationQuery::parse(location)? {
+            LocationQuery::Id(id) => match self.get_weather_data(id).await {
+                // Numeric postcodes and ZIP codes look like ids, so search for them by name instead
+                Err(err) if is_not_found(err.as_ref()) => {
+                    let name = LocationQuery::Name(normalise_location(location)?);
+                    self.get_weather_data(self.get_location_id(&name).await?)
+                        .await?
+                }
+                body => body?,
+            },
+            query => {
+                self.get_weather_data(self.get_location_id(&query).await?)
+                    .await?
+            }
+        };
+        let response = serde_json::from_str(&body)?;
+
+        if let Some(cache) = &self.cache {
+            // A failure to cache shouldn't stop us showing the weather
+            let _ = cache.put(location, &body);
+        }
         Ok(response)
     }
 
-    async fn get_location_id(&self, location: &str) -> Result<usize, Box<dyn std::error::Error>> {
-        const ENDPOINT: &str = "https://open.live.bbc.co.uk/locator/locations?filter=international&place-types=settlement,airport,district&s={}&format=json&order=importance&a=true";
+    /// Lists the places matching a location, best match first.
+    pub async fn search_locations(
+        &self,
+        location: &str,
+    ) -> Result<Vec<WeatherLocationResult>, Box<dyn std::error::Error + Send + Sync>> {
+        let query = match LocationQuery::parse(location)? {
+            // Only names can be searched for, and digits may be a postcode or ZIP code
+            LocationQuery::Id(_) => LocationQuery::Name(normalise_location(location)?),
+            query => query,
+        };
+        self.locate(&query).await
+    }
+
+    async fn get_location_id(
+        &self,
+        query: &LocationQuery,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        if let LocationQuery::Id(id) = query {
+            return Ok(*id);
+        }
 
-        let response: WeatherLocationResponse = reqwest::get(ENDPOINT.replace("{}", location))
-            .await?
-            .json()
-            .await?;
-        if response.response.results.total_results == 0 {
+        let results = self.locate(query).await?;
+        let Some(result) = results.first() else {
             return Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
+                std::io::ErrorKind::NotFound,
                 "No results found",
             )));
-        }
+        };
 
-        let id = response.response.results.results[0].id.parse().unwrap();
+        let id = result.id.parse()?;
         Ok(id)
     }
 
+    async fn locate(
+        &self,
+        query: &LocationQuery,
+    ) -> Result<Vec<WeatherLocationResult>, Box<dyn std::error::Error + Send + Sync>> {
+        let endpoint = &self.locator_endpoint;
+        let url = match query {
+            LocationQuery::Id(_) => {
+                return Err(query::invalid_location(
+                    "Location ids can't be searched for",
+                ))
+            }
+            LocationQuery::Name(name) => query::location_search_url(endpoint, name)?,
+            LocationQuery::Postcode(postcode) => query::postcode_search_url(endpoint, postcode)?,
+            LocationQuery::Coordinates {
+                latitude,
+                longitude,
+            } => query::coordinates_search_url(endpoint, *latitude, *longitude)?,
+        };
+
+        let started = Instant::now();
+        let response: Result<WeatherLocationResponse, reqwest::Error> = async {
+            self.client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await
+        }
+        .await;
+        self.metrics
+            .record(Endpoint::Locator, started.elapsed(), response.is_ok());
+
+        let mut results = response?.response.results.results;
+
+        if let LocationQuery::Coordinates {
+            latitude,
+            longitude,
+        } = query
+        {
+            query::sort_by_distance(&mut results, *latitude, *longitude);
+        }
+        Ok(results)
+    }
+
+    /// Fetches the raw forecast body for a location id.
     async fn get_weather_data(
         &self,
         id: usize,
-    ) -> Result<WeatherForecastResponse, Box<dyn std::error::Error>> {
-        const ENDPOINT: &str =
-            "https://weather-broker-cdn.api.bbci.co.uk/en/forecast/aggregated/{}";
-        let response: WeatherForecastResponse =
-            reqwest::get(ENDPOINT.replace("{}", id.to_string().as_str()))
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let started = Instant::now();
+        let body: Result<String, reqwest::Error> = async {
+            self.client
+                .get(format!("{}/{}", self.forecast_endpoint, id))
+                .send()
                 .await?
-                .json()
-                .await?;
-        Ok(response)
+                .error_for_status()?
+                .text()
+                .await
+        }
+        .await;
+        self.metrics
+            .record(Endpoint::Forecast, started.elapsed(), body.is_ok());
+        Ok(body?)
     }
 }
 
-pub(crate) enum WeathemaComponentMessaging {
-    Waiting,
-    Received(WeatherForecastResponse),
-    Error(String),
+/// Whether the forecast service has no forecast for a location id.
+pub(crate) fn is_not_found(err: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        == Some(reqwest::StatusCode::NOT_FOUND)
+}
+
+/// One of the dashboard's locations, with its forecast or why it couldn't be fetched.
+#[derive(Clone)]
+pub(crate) struct LocationForecast {
+    pub location: String,
+    pub forecast: Result<WeatherForecastResponse, String>,
+}
+
+pub(crate) enum WeathemaCommand {
+    Fetch(String),
+    Refresh,
+    Cancel,
+    ChangeUnits(Units),
+    Export,
+    DismissAlerts,
+    ToggleDashboard,
+    /// Moves the day shown in detail along the forecast
+    NextDay,
+    PreviousDay,
+    Quit,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub(crate) enum Units {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+impl Units {
+    pub fn toggle(self) -> Self {
+        match self {
+            Units::Metric => Units::Imperial,
+            Units::Imperial => Units::Metric,
+        }
+    }
+
+    pub fn temperature(self, celsius: f64) -> f64 {
+        match self {
+            Units::Metric => celsius,
+            Units::Imperial => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    pub fn speed(self, kph: f64) -> f64 {
+        match self {
+            Units::Metric => kph,
+            Units::Imperial => kph / 1.609_344,
+        }
+    }
+
+    pub fn temperature_symbol(self) -> &'static str {
+        match self {
+            Units::Metric => "C",
+            Units::Imperial => "F",
+        }
+    }
+
+    pub fn speed_symbol(self) -> &'static str {
+        match self {
+            Units::Metric => "kph",
+            Units::Imperial => "mph",
+        }
+    }
+}
+
+/// Broad categories of the BBC/Met Office weather type codes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum WeatherType {
+    Unknown,
+    Sunny,
+    PartlyCloudy,
+    Cloudy,
+    Rainy,
+    Snowy,
+    Stormy,
+}
+
+impl WeatherType {
+    pub fn from_code(weather_type: u8) -> Self {
+        match weather_type {
+            0..=1 => WeatherType::Sunny,
+            2..=4 => WeatherType::PartlyCloudy,
+            5..=8 => WeatherType::Cloudy,
+            9..=15 => WeatherType::Rainy,
+            16..=27 => WeatherType::Snowy,
+            28..=30 => WeatherType::Stormy,
+            _ => WeatherType::Unknown,
+        }
+    }
+
+    pub fn image_path(self) -> &'static str {
+        match self {
+            WeatherType::Sunny => "src/images/sunny.txt",
+            WeatherType::PartlyCloudy => "src/images/partly-cloudy.txt",
+            WeatherType::Cloudy => "src/images/cloudy.txt",
+            WeatherType::Rainy => "src/images/rainy.txt",
+            WeatherType::Snowy => "src/images/snowy.txt",
+            WeatherType::Stormy => "src/images/stormy.txt",
+            WeatherType::Unknown => "src/images/unknown.txt",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            WeatherType::Sunny => "☀",
+            WeatherType::PartlyCloudy => "⛅",
+            WeatherType::Cloudy => "☁",
+            WeatherType::Rainy => "🌧",
+            WeatherType::Snowy => "❄",
+            WeatherType::Stormy => "⛈",
+            WeatherType::Unknown => "?",
+        }
+    }
 }
 
 ////////////// Weather API //////////////
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherForecastResponse {
     pub forecasts: Vec<WeatherForecast>,
+    pub location: WeatherForecastLocation,
+}
+
+impl WeatherForecastResponse {
+    /// Every hourly report of the forecast, day after day.
+    pub fn hourly_reports(&self) -> impl Iterator<Item = &WeatherDetailedReport> {
+        self.forecasts.iter().flat_map(|day| &day.detailed.reports)
+    }
+
+    /// The position in `hourly_reports` of the hour under way at the location.
+    ///
+    /// The hourly reports start at the hour the forecast was issued, so before then it is the
+    /// first.
+    pub fn current_hour(&self, now: DateTime<Utc>) -> usize {
+        let now = sun::local_now(self, now);
+        self.hourly_reports()
+            .enumerate()
+            .filter(|(_, report)| report.local_time().is_some_and(|time| time <= now))
+            .last()
+            .map_or(0, |(hour, _)| hour)
+    }
+
+    /// The report for the hour under way at the location, see `current_hour`.
+    pub fn current_report(&self, now: DateTime<Utc>) -> Option<&WeatherDetailedReport> {
+        self.hourly_reports().nth(self.current_hour(now))
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct WeatherForecastLocation {
+    pub id: String,
+    pub name: String,
+    pub container: String,
+    pub latitude: f64,
+    pub longitude: f64,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherForecast {
     pub detailed: WeatherDetailedForecast,
     pub summary: WeatherSummaryForecast,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherDetailedForecast {
     #[serde(rename = "issueDate")]
     pub issue_date: String,
@@ -79,16 +377,83 @@ pub(crate) struct WeatherDetailedForecast {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherDetailedReport {
+    #[serde(rename = "localDate")]
+    pub local_date: String,
+    pub timeslot: String,
     #[serde(rename = "weatherType")]
     pub weather_type: u8,
     #[serde(rename = "weatherTypeText")]
     pub weather_type_text: String,
+    #[serde(rename = "temperatureC")]
+    pub temperature_c: f64,
+    #[serde(rename = "feelsLikeTemperatureC")]
+    pub feels_like_temperature_c: Option<f64>,
+    #[serde(rename = "windSpeedKph")]
+    pub wind_speed_kph: f64,
+    #[serde(rename = "gustSpeedKph")]
+    pub gust_speed_kph: Option<f64>,
+    #[serde(rename = "windDirection")]
+    pub wind_direction: String,
+    #[serde(rename = "precipitationProbabilityInPercent")]
+    pub precipitation_probability_in_percent: f64,
+    pub humidity: Option<f64>,
+    /// Mean sea level pressure in millibars
+    pub pressure: Option<f64>,
+    #[serde(default, deserialize_with = "visibility::deserialize")]
+    pub visibility: Option<visibility::Visibility>,
+}
+
+impl WeatherDetailedReport {
+    /// The local date and time the report starts at.
+    pub fn local_time(&self) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(
+            &format!("{} {}", self.local_date, self.timeslot),
+            "%Y-%m-%d %H:%M",
+        )
+        .ok()
+    }
+
+    /// The reported feels like temperature, or one worked out from the conditions.
+    pub fn feels_like_c(&self) -> f64 {
+        self.feels_like_temperature_c.unwrap_or_else(|| {
+            meteo::feels_like(self.temperature_c, self.humidity, self.wind_speed_kph)
+        })
+    }
+
+    pub fn dew_point_c(&self) -> Option<f64> {
+        self.humidity
+            .and_then(|humidity| meteo::dew_point(self.temperature_c, humidity))
+    }
+
+    /// The heat index, when it is warm enough for one.
+    pub fn heat_index_c(&self) -> Option<f64> {
+        self.humidity
+            .filter(|_| meteo::heat_index_applies(self.temperature_c))
+            .map(|humidity| meteo::heat_index(self.temperature_c, humidity))
+    }
+
+    /// The wind chill, when it is cold and windy enough for one.
+    pub fn wind_chill_c(&self) -> Option<f64> {
+        meteo::wind_chill_applies(self.temperature_c, self.wind_speed_kph)
+            .then(|| meteo::wind_chill(self.temperature_c, self.wind_speed_kph))
+    }
+
+    pub fn humidex_c(&self) -> Option<f64> {
+        self.dew_point_c()
+            .map(|dew_point| meteo::humidex(self.temperature_c, dew_point))
+    }
+
+    pub fn apparent_temperature_c(&self) -> Option<f64> {
+        self.humidity.map(|humidity| {
+            meteo::apparent_temperature(self.temperature_c, humidity, self.wind_speed_kph)
+        })
+    }
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherSummaryForecast {
     #[serde(rename = "issueDate")]
     pub issue_date: String,
@@ -98,16 +463,35 @@ pub(crate) struct WeatherSummaryForecast {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherSummaryReport {
-    pub sunrise: String,
-    pub sunset: String,
+    #[serde(rename = "localDate")]
+    pub local_date: String,
+    /// `None` when the forecast leaves it out, the sun arc then works it out from the location
+    #[serde(default)]
+    pub sunrise: Option<String>,
+    #[serde(default)]
+    pub sunset: Option<String>,
     #[serde(rename = "maxTempC")]
     pub max_temp_c: f64,
     #[serde(rename = "minTempC")]
     pub min_temp_c: f64,
+    #[serde(rename = "lowermaxTemperatureC")]
+    pub lower_max_temp_c: Option<f64>,
+    #[serde(rename = "uppermaxTemperatureC")]
+    pub upper_max_temp_c: Option<f64>,
+    #[serde(rename = "lowerminTemperatureC")]
+    pub lower_min_temp_c: Option<f64>,
+    #[serde(rename = "upperminTemperatureC")]
+    pub upper_min_temp_c: Option<f64>,
+    #[serde(rename = "mostLikelyHighTemperatureC")]
+    pub most_likely_high_temp_c: Option<f64>,
+    #[serde(rename = "mostLikelyLowTemperatureC")]
+    pub most_likely_low_temp_c: Option<f64>,
     #[serde(rename = "windSpeedKph")]
     pub wind_speed_kph: f64,
+    #[serde(rename = "gustSpeedKph")]
+    pub gust_speed_kph: Option<f64>,
     #[serde(rename = "windDirection")]
     pub wind_direction: String,
     #[serde(rename = "weatherType")]
@@ -117,20 +501,56 @@ pub(crate) struct WeatherSummaryReport {
 
     #[serde(rename = "precipitationProbabilityInPercent")]
     pub precipitation_probability_in_percent: f64,
+    #[serde(rename = "uvIndex")]
+    pub uv_index: Option<f64>,
+    #[serde(rename = "uvIndexBand")]
+    pub uv_index_band: Option<String>,
+    #[serde(rename = "uvIndexText")]
+    pub uv_index_text: Option<String>,
+    #[serde(rename = "pollenIndex")]
+    pub pollen_index: Option<f64>,
+    #[serde(rename = "pollenIndexBand")]
+    pub pollen_index_band: Option<String>,
+    #[serde(rename = "pollenIndexText")]
+    pub pollen_index_text: Option<String>,
+    #[serde(rename = "pollutionIndex")]
+    pub pollution_index: Option<f64>,
+    #[serde(rename = "pollutionIndexBand")]
+    pub pollution_index_band: Option<String>,
+    #[serde(rename = "pollutionIndexText")]
+    pub pollution_index_text: Option<String>,
+}
+
+impl WeatherSummaryReport {
+    /// The range the max temperature is likely to fall in, `None` unless the forecast gives both
+    /// bounds.
+    pub fn max_temp_range_c(&self) -> Option<(f64, f64)> {
+        Self::range(self.lower_max_temp_c, self.upper_max_temp_c)
+    }
+
+    /// The range the min temperature is likely to fall in, see `max_temp_range_c`.
+    pub fn min_temp_range_c(&self) -> Option<(f64, f64)> {
+        Self::range(self.lower_min_temp_c, self.upper_min_temp_c)
+    }
+
+    fn range(lower: Option<f64>, upper: Option<f64>) -> Option<(f64, f64)> {
+        let (lower, upper) = (lower?, upper?);
+        (lower < upper).then_some((lower, upper))
+    }
 }
 
 ////////////// Location API //////////////
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherLocationResponse {
     pub response: WeatherLocationWrappedResults,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherLocationWrappedResults {
     pub results: WeatherLocationResults,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherLocationResults {
     pub results: Vec<WeatherLocationResult>,
     #[serde(rename = "totalResults")]
@@ -138,7 +558,7 @@ pub(crate) struct WeatherLocationResults {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub(crate) struct WeatherLocationResult {
     pub id: String,
     pub name: String,
@@ -148,10 +568,19 @@ pub(crate) struct WeatherLocationResult {
     pub longitude: f64,
 }
 
+/// The two week forecast for Dyserth the tests share.
+#[cfg(test)]
+pub(crate) fn test_forecast() -> WeatherForecastResponse {
+    let file = std::fs::File::open("src/test_data/weather_forecast.json").unwrap();
+    serde_json::from_reader(file).unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
     use serde_json::json;
+    use std::sync::atomic::Ordering;
 
     #[test]
     fn test() {
@@ -231,4 +660,138 @@ mod tests {
         assert_eq!(result.forecasts.len(), 14);
         assert_eq!(result.forecasts[0].summary.report.weather_type, 3);
     }
+
+    #[test]
+    fn reads_where_and_when_the_forecast_is_for() {
+        let result = test_forecast();
+
+        assert_eq!(result.forecasts[0].summary.report.local_date, "2024-08-29");
+        assert_eq!(result.forecasts[0].detailed.reports[0].timeslot, "15:00");
+        assert_eq!(result.location.name, "Dyserth");
+    }
+
+    #[test]
+    fn derives_missing_feels_like_temperatures() {
+        let result = test_forecast();
+        let mut report = result.forecasts[0].detailed.reports[0].clone();
+
+        assert_eq!(report.temperature_c, 18.0);
+        assert_eq!(report.feels_like_temperature_c, Some(18.0));
+        assert_eq!(report.humidity, Some(61.0));
+        assert_eq!(report.dew_point_c().map(f64::round), Some(10.0));
+
+        report.feels_like_temperature_c = None;
+        report.temperature_c = 4.0;
+        report.wind_speed_kph = 30.0;
+        assert_eq!(report.feels_like_c().round(), -1.0);
+    }
+
+    #[test]
+    fn derives_comfort_indices_where_they_apply() {
+        let result = test_forecast();
+        let mut report = result.forecasts[0].detailed.reports[0].clone();
+
+        // 18°C at 61% in a 17 km/h wind is neither hot nor cold enough for either index
+        assert_eq!(report.heat_index_c(), None);
+        assert_eq!(report.wind_chill_c(), None);
+        assert_eq!(report.humidex_c().map(f64::round), Some(19.0));
+        assert_eq!(report.apparent_temperature_c().map(f64::round), Some(15.0));
+
+        report.temperature_c = 32.0;
+        report.humidity = Some(70.0);
+        assert_eq!(report.heat_index_c(), Some(meteo::heat_index(32.0, 70.0)));
+
+        report.temperature_c = 4.0;
+        report.wind_speed_kph = 30.0;
+        assert_eq!(report.wind_chill_c().map(f64::round), Some(-1.0));
+
+        report.humidity = None;
+        assert_eq!(report.humidex_c(), None);
+        assert_eq!(report.apparent_temperature_c(), None);
+    }
+
+    #[test]
+    fn ranges_temperatures_between_their_bounds() {
+        let result = test_forecast();
+
+        // Only the most likely high and low are given, which aren't a range
+        let today = &result.forecasts[0].summary.report;
+        assert_eq!(today.most_likely_high_temp_c, Some(19.0));
+        assert_eq!(today.max_temp_range_c(), None);
+        assert_eq!(today.min_temp_range_c(), None);
+
+        let mut bounded = today.clone();
+        bounded.lower_max_temp_c = Some(16.0);
+        bounded.upper_max_temp_c = Some(21.0);
+        assert_eq!(bounded.max_temp_range_c(), Some((16.0, 21.0)));
+
+        bounded.upper_min_temp_c = Some(10.0);
+        assert_eq!(bounded.min_temp_range_c(), None);
+    }
+
+    #[tokio::test]
+    async fn fetches_a_location_by_id() {
+        let (weather_api, upstream) = upstream::start().await;
+
+        let forecast = weather_api
+            .get_weather(upstream::LOCATION_ID)
+            .await
+            .unwrap();
+        assert_eq!(forecast.location.name, "Dyserth");
+        assert_eq!(upstream.locator_requests.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn searches_for_numbers_that_are_not_ids() {
+        let (weather_api, upstream) = upstream::start().await;
+
+        let forecast = weather_api
+            .get_weather(upstream::NUMERIC_POSTCODE)
+            .await
+            .unwrap();
+        assert_eq!(forecast.location.name, "Dyserth");
+        assert_eq!(upstream.locator_requests.load(Ordering::SeqCst), 1);
+
+        let results = weather_api
+            .search_locations(upstream::NUMERIC_POSTCODE)
+            .await
+            .unwrap();
+        assert_eq!(results[0].id, upstream::LOCATION_ID);
+
+        let err = weather_api.get_weather("1").await.unwrap_err();
+        assert_eq!(err.to_string(), "No results found");
+    }
+
+    #[test]
+    fn finds_the_current_hour() {
+        let forecast = test_forecast();
+        let at =
+            |hour: u32, minute: u32| Utc.with_ymd_and_hms(2024, 8, 29, hour, minute, 0).unwrap();
+
+        // Issued at 14:00 BST, before the first report at 15:00
+        assert_eq!(forecast.current_hour(at(13, 0)), 0);
+        assert_eq!(forecast.current_hour(at(14, 59)), 0);
+        assert_eq!(forecast.current_hour(at(15, 0)), 1);
+        assert_eq!(
+            forecast.current_report(at(15, 30)).unwrap().timeslot,
+            "16:00"
+        );
+        // Past the end of the forecast
+        let last = forecast.hourly_reports().count() - 1;
+        assert_eq!(
+            forecast.current_hour(at(23, 0) + chrono::TimeDelta::days(30)),
+            last
+        );
+    }
+
+    #[test]
+    fn maps_weather_types() {
+        assert_eq!(WeatherType::from_code(1), WeatherType::Sunny);
+        assert_eq!(WeatherType::from_code(3), WeatherType::PartlyCloudy);
+        assert_eq!(WeatherType::from_code(7), WeatherType::Cloudy);
+        assert_eq!(WeatherType::from_code(14), WeatherType::Rainy);
+        assert_eq!(WeatherType::from_code(24), WeatherType::Snowy);
+        assert_eq!(WeatherType::from_code(30), WeatherType::Stormy);
+        assert_eq!(WeatherType::from_code(99), WeatherType::Unknown);
+    }
 }
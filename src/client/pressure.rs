@@ -0,0 +1,240 @@
+use crate::client::WeatherForecastResponse;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+
+/// How far the trend looks either side of now, as the Met Office reports pressure tendency.
+const TENDENCY_HOURS: i64 = 3;
+
+/// Changes smaller than this over three hours count as steady, in millibars.
+const STEADY_MB: f64 = 1.0;
+
+/// How many hours the sparkline covers.
+const SPARKLINE_HOURS: usize = 24;
+
+/// Where a barometer's needle would point, worded as on a classic aneroid dial.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Reading {
+    Stormy,
+    Rain,
+    Change,
+    Fair,
+    VeryDry,
+}
+
+/// The upper limit of each reading in millibars, halfway between the marks on the dial at 28,
+/// 28¾, 29½, 30¼ and 31 inches of mercury.
+const READING_LIMITS: [(f64, Reading); 4] = [
+    (961.0, Reading::Stormy),
+    (986.0, Reading::Rain),
+    (1011.0, Reading::Change),
+    (1037.0, Reading::Fair),
+];
+
+impl Reading {
+    pub fn from_pressure(pressure: f64) -> Self {
+        READING_LIMITS
+            .iter()
+            .find(|(limit, _)| pressure < *limit)
+            .map_or(Reading::VeryDry, |(_, reading)| *reading)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Reading::Stormy => "Stormy",
+            Reading::Rain => "Rain",
+            Reading::Change => "Change",
+            Reading::Fair => "Fair",
+            Reading::VeryDry => "Very Dry",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    pub fn arrow(self) -> char {
+        match self {
+            Trend::Rising => '↗',
+            Trend::Falling => '↘',
+            Trend::Steady => '→',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Trend::Rising => "Rising",
+            Trend::Falling => "Falling",
+            Trend::Steady => "Steady",
+        }
+    }
+}
+
+/// How the pressure changes over three hours.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Tendency {
+    pub trend: Trend,
+    /// In millibars
+    pub change: f64,
+}
+
+impl Tendency {
+    fn new(change: f64) -> Self {
+        let trend = if change.abs() < STEADY_MB {
+            Trend::Steady
+        } else if change > 0.0 {
+            Trend::Rising
+        } else {
+            Trend::Falling
+        };
+        Self { trend, change }
+    }
+
+    /// The trend and change, such as `↗ Rising +2 mb`.
+    pub fn describe(&self) -> String {
+        match self.trend {
+            Trend::Steady => format!("{} {}", self.trend.arrow(), self.trend.name()),
+            _ => format!(
+                "{} {} {:+.0} mb",
+                self.trend.arrow(),
+                self.trend.name(),
+                self.change
+            ),
+        }
+    }
+}
+
+/// The pressure now and how it is changing, from the hourly reports.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Barometer {
+    /// Mean sea level pressure in millibars
+    pub pressure: f64,
+    pub reading: Reading,
+    /// Over the last three hours, when the forecast still covers them
+    pub past: Option<Tendency>,
+    /// Over the next three hours, when the forecast reaches that far
+    pub coming: Option<Tendency>,
+    /// The pressure each hour from now, for a day
+    pub hourly: Vec<f64>,
+}
+
+impl Barometer {
+    /// Reads the barometer for the hour under way at the location, `None` when the forecast has
+    /// no pressure for it.
+    ///
+    /// The hourly reports start at the hour the forecast was issued, so the last three hours are
+    /// only known once it is a few hours old.
+    pub fn read(forecast: &WeatherForecastResponse, now: DateTime<Utc>) -> Option<Self> {
+        let current = forecast.current_hour(now);
+        let report = forecast.current_report(now)?;
+        let (time, pressure) = (report.local_time()?, report.pressure?);
+        let timeline = timeline(forecast);
+
+        let window = TimeDelta::hours(TENDENCY_HOURS);
+        let at = |time: NaiveDateTime| {
+            timeline
+                .iter()
+                .find(|(hour, _)| *hour == time)
+                .map(|(_, pressure)| *pressure)
+        };
+
+        Some(Self {
+            pressure,
+            reading: Reading::from_pressure(pressure),
+            past: at(time - window).map(|before| Tendency::new(pressure - before)),
+            coming: at(time + window).map(|after| Tendency::new(after - pressure)),
+            hourly: forecast
+                .hourly_reports()
+                .skip(current)
+                .filter_map(|report| report.pressure)
+                .take(SPARKLINE_HOURS)
+                .collect(),
+        })
+    }
+
+    /// The trend so far where it is known, otherwise the one forecast.
+    pub fn tendency(&self) -> Option<Tendency> {
+        self.past.or(self.coming)
+    }
+}
+
+/// The local time and pressure of every hourly report that has one, in order.
+fn timeline(forecast: &WeatherForecastResponse) -> Vec<(NaiveDateTime, f64)> {
+    forecast
+        .hourly_reports()
+        .filter_map(|report| Some((report.local_time()?, report.pressure?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use chrono::{FixedOffset, TimeZone};
+
+    /// A time at the forecast's location, on BST.
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        FixedOffset::east_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 8, day, hour, minute, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn words_the_dial() {
+        assert_eq!(Reading::from_pressure(950.0), Reading::Stormy);
+        assert_eq!(Reading::from_pressure(975.0), Reading::Rain);
+        assert_eq!(Reading::from_pressure(1000.0), Reading::Change);
+        assert_eq!(Reading::from_pressure(1017.0), Reading::Fair);
+        assert_eq!(Reading::from_pressure(1045.0), Reading::VeryDry);
+        assert_eq!(Reading::VeryDry.name(), "Very Dry");
+    }
+
+    #[test]
+    fn grades_the_tendency() {
+        assert_eq!(Tendency::new(2.0).trend, Trend::Rising);
+        assert_eq!(Tendency::new(-3.0).trend, Trend::Falling);
+        assert_eq!(Tendency::new(0.5).trend, Trend::Steady);
+        assert_eq!(Tendency::new(2.0).describe(), "↗ Rising +2 mb");
+        assert_eq!(Tendency::new(-3.0).describe(), "↘ Falling -3 mb");
+        assert_eq!(Tendency::new(0.0).describe(), "→ Steady");
+    }
+
+    #[test]
+    fn reads_a_fresh_forecast_from_its_first_hour() {
+        // Issued at 14:00, the first report is for 15:00
+        let barometer = Barometer::read(&test_forecast(), at(29, 14, 0)).unwrap();
+        assert_eq!(barometer.pressure, 1017.0);
+        assert_eq!(barometer.reading, Reading::Fair);
+        assert_eq!(barometer.past, None);
+        // 1019 mb at 18:00
+        assert_eq!(barometer.coming, Some(Tendency::new(2.0)));
+        assert_eq!(barometer.tendency(), barometer.coming);
+        assert_eq!(barometer.hourly.len(), 24);
+        assert_eq!(barometer.hourly[..3], [1017.0, 1018.0, 1018.0]);
+    }
+
+    #[test]
+    fn looks_back_once_the_forecast_has_aged() {
+        let barometer = Barometer::read(&test_forecast(), at(29, 18, 30)).unwrap();
+        assert_eq!(barometer.pressure, 1019.0);
+        assert_eq!(barometer.past, Some(Tendency::new(2.0)));
+        assert_eq!(barometer.tendency(), barometer.past);
+        assert_eq!(barometer.hourly[0], 1019.0);
+    }
+
+    #[test]
+    fn needs_a_pressure() {
+        let mut forecast = test_forecast();
+        for day in &mut forecast.forecasts {
+            for report in &mut day.detailed.reports {
+                report.pressure = None;
+            }
+        }
+        assert_eq!(Barometer::read(&forecast, at(29, 14, 0)), None);
+    }
+}
@@ -0,0 +1,395 @@
+use super::WeatherLocationResult;
+use reqwest::Url;
+use unicode_normalization::UnicodeNormalization;
+
+pub(crate) const LOCATOR_ENDPOINT: &str = "https://open.live.bbc.co.uk/locator/locations";
+const MAX_LOCATION_LENGTH: usize = 100;
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// The different ways a location can be looked up.
+#[derive(Debug, PartialEq)]
+pub(crate) enum LocationQuery {
+    /// A free-text place name, e.g. `Dyserth`
+    Name(String),
+    /// A `lat,lon` pair in decimal degrees, e.g. `53.30,-3.41`
+    Coordinates { latitude: f64, longitude: f64 },
+    /// A UK postcode or postcode district, e.g. `LL18 6AA` or `LL18`
+    Postcode(String),
+    /// A BBC location id, e.g. `2650584`
+    Id(usize),
+}
+
+impl LocationQuery {
+    pub fn parse(location: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let location = normalise_location(location)?;
+
+        // Taken for an id first, falling back to a name search if the BBC doesn't know it
+        if location.chars().all(|c| c.is_ascii_digit()) {
+            if let Ok(id) = location.parse() {
+                return Ok(LocationQuery::Id(id));
+            }
+        }
+
+        if let Some((latitude, longitude)) = parse_coordinates(&location) {
+            if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                return Err(invalid_location("Coordinates are out of range"));
+            }
+            return Ok(LocationQuery::Coordinates {
+                latitude,
+                longitude,
+            });
+        }
+
+        if let Some(postcode) = parse_postcode(&location) {
+            return Ok(LocationQuery::Postcode(postcode));
+        }
+
+        Ok(LocationQuery::Name(location))
+    }
+}
+
+fn parse_coordinates(location: &str) -> Option<(f64, f64)> {
+    let (latitude, longitude) = location.split_once(',')?;
+    let latitude = latitude.trim().parse::<f64>().ok()?;
+    let longitude = longitude.trim().parse::<f64>().ok()?;
+    if latitude.is_finite() && longitude.is_finite() {
+        Some((latitude, longitude))
+    } else {
+        None
+    }
+}
+
+/// Recognises a full UK postcode (`LL18 6AA`) or just its outward code (`LL18`), returning it
+/// upper-cased with a single space before the inward code.
+fn parse_postcode(location: &str) -> Option<String> {
+    let compact = location.replace(' ', "").to_ascii_uppercase();
+    if !compact.is_ascii() {
+        return None;
+    }
+
+    let is_outward = |code: &str| {
+        let bytes = code.as_bytes();
+        let letters = bytes.iter().take_while(|b| b.is_ascii_alphabetic()).count();
+        let rest = &bytes[letters..];
+        (1..=2).contains(&letters)
+            && !rest.is_empty()
+            && rest.len() <= 2
+            && rest[0].is_ascii_digit()
+            && rest[1..].iter().all(|b| b.is_ascii_alphanumeric())
+    };
+
+    if is_outward(&compact) {
+        return Some(compact);
+    }
+
+    if compact.len() > 3 {
+        let (outward, inward) = compact.split_at(compact.len() - 3);
+        let inward_bytes = inward.as_bytes();
+        if is_outward(outward)
+            && inward_bytes[0].is_ascii_digit()
+            && inward_bytes[1..].iter().all(|b| b.is_ascii_alphabetic())
+        {
+            return Some(format!("{} {}", outward, inward));
+        }
+    }
+
+    None
+}
+
+/// Tidies up a location as typed by the user so that it can be sent to the locator.
+///
+/// Surrounding whitespace is trimmed, runs of whitespace are collapsed to a single space and the
+/// text is converted to Unicode NFC so that composed and decomposed accents search the same.
+pub(crate) fn normalise_location(
+    location: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let normalised = location
+        .nfc()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if normalised.is_empty() {
+        return Err(invalid_location("Location is empty"));
+    }
+    if normalised.chars().any(char::is_control) {
+        return Err(invalid_location("Location contains control characters"));
+    }
+    if normalised.chars().count() > MAX_LOCATION_LENGTH {
+        return Err(invalid_location("Location is too long"));
+    }
+
+    Ok(normalised)
+}
+
+/// Builds the locator search URL, percent-encoding the location as a query parameter.
+pub(crate) fn location_search_url(
+    endpoint: &str,
+    location: &str,
+) -> Result<Url, Box<dyn std::error::Error + Send + Sync>> {
+    let url = Url::parse_with_params(
+        endpoint,
+        &[
+            ("filter", "international"),
+            ("place-types", "settlement,airport,district"),
+            ("s", location),
+            ("format", "json"),
+            ("order", "importance"),
+            ("a", "true"),
+        ],
+    )?;
+    Ok(url)
+}
+
+/// Builds the locator search URL for a postcode, searching on its outward code.
+pub(crate) fn postcode_search_url(
+    endpoint: &str,
+    postcode: &str,
+) -> Result<Url, Box<dyn std::error::Error + Send + Sync>> {
+    let outward = postcode.split(' ').next().unwrap_or(postcode);
+    let url = Url::parse_with_params(
+        endpoint,
+        &[
+            ("filter", "domestic"),
+            ("place-types", "postcode"),
+            ("s", outward),
+            ("format", "json"),
+            ("a", "true"),
+        ],
+    )?;
+    Ok(url)
+}
+
+/// Builds the locator URL listing the locations around a pair of coordinates.
+pub(crate) fn coordinates_search_url(
+    endpoint: &str,
+    latitude: f64,
+    longitude: f64,
+) -> Result<Url, Box<dyn std::error::Error + Send + Sync>> {
+    let url = Url::parse_with_params(
+        endpoint,
+        &[
+            ("filter", "international"),
+            ("place-types", "settlement,airport,district"),
+            ("la", latitude.to_string().as_str()),
+            ("lo", longitude.to_string().as_str()),
+            ("format", "json"),
+            ("a", "true"),
+        ],
+    )?;
+    Ok(url)
+}
+
+/// Orders locator results so that the one closest to the given coordinates comes first.
+pub(crate) fn sort_by_distance(
+    results: &mut [WeatherLocationResult],
+    latitude: f64,
+    longitude: f64,
+) {
+    results.sort_by(|a, b| {
+        let a = distance_km(latitude, longitude, a.latitude, a.longitude);
+        let b = distance_km(latitude, longitude, b.latitude, b.longitude);
+        a.total_cmp(&b)
+    });
+}
+
+/// Great-circle distance between two points using the haversine formula.
+fn distance_km(latitude1: f64, longitude1: f64, latitude2: f64, longitude2: f64) -> f64 {
+    let d_latitude = (latitude2 - latitude1).to_radians();
+    let d_longitude = (longitude2 - longitude1).to_radians();
+    let a = (d_latitude / 2.0).sin().powi(2)
+        + latitude1.to_radians().cos()
+            * latitude2.to_radians().cos()
+            * (d_longitude / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+pub(crate) fn invalid_location(reason: &str) -> Box<dyn std::error::Error + Send + Sync> {
+    Box::new(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        reason.to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_term(url: &Url) -> String {
+        url.query_pairs()
+            .find(|(key, _)| key == "s")
+            .map(|(_, value)| value.into_owned())
+            .unwrap()
+    }
+
+    #[test]
+    fn trims_and_collapses_whitespace() {
+        assert_eq!(
+            normalise_location("  Llanfair \t  PG \n").unwrap(),
+            "Llanfair PG"
+        );
+    }
+
+    #[test]
+    fn composes_decomposed_accents() {
+        assert_eq!(normalise_location("Sa\u{303}o Paulo").unwrap(), "São Paulo");
+        assert_eq!(normalise_location("Zu\u{308}rich").unwrap(), "Zürich");
+    }
+
+    #[test]
+    fn rejects_invalid_locations() {
+        assert!(normalise_location("").is_err());
+        assert!(normalise_location("   ").is_err());
+        assert!(normalise_location("Dyserth\u{7}").is_err());
+        assert!(normalise_location(&"a".repeat(MAX_LOCATION_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn encodes_spaces() {
+        let url = location_search_url(LOCATOR_ENDPOINT, "Llanfair PG").unwrap();
+        assert!(url.as_str().contains("s=Llanfair+PG&"));
+        assert_eq!(search_term(&url), "Llanfair PG");
+    }
+
+    #[test]
+    fn encodes_ampersands_without_injecting_parameters() {
+        let url = location_search_url(LOCATOR_ENDPOINT, "AT&T Park").unwrap();
+        assert!(url.as_str().contains("s=AT%26T+Park&"));
+        assert_eq!(search_term(&url), "AT&T Park");
+        assert_eq!(url.query_pairs().count(), 6);
+    }
+
+    #[test]
+    fn encodes_fragments() {
+        let url = location_search_url(LOCATOR_ENDPOINT, "Dyserth#format=xml").unwrap();
+        assert_eq!(url.fragment(), None);
+        assert_eq!(search_term(&url), "Dyserth#format=xml");
+    }
+
+    #[test]
+    fn encodes_accents() {
+        let url = location_search_url(LOCATOR_ENDPOINT, "São Paulo").unwrap();
+        assert!(url.as_str().contains("s=S%C3%A3o+Paulo&"));
+        assert_eq!(search_term(&url), "São Paulo");
+
+        let url = location_search_url(LOCATOR_ENDPOINT, "Zürich").unwrap();
+        assert!(url.as_str().contains("s=Z%C3%BCrich&"));
+        assert_eq!(search_term(&url), "Zürich");
+    }
+
+    #[test]
+    fn parses_location_ids() {
+        assert_eq!(
+            LocationQuery::parse(" 2650584 ").unwrap(),
+            LocationQuery::Id(2650584)
+        );
+        // Too long for an id
+        assert_eq!(
+            LocationQuery::parse("123456789012345678901234567890").unwrap(),
+            LocationQuery::Name("123456789012345678901234567890".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_coordinates() {
+        assert_eq!(
+            LocationQuery::parse("53.30,-3.41").unwrap(),
+            LocationQuery::Coordinates {
+                latitude: 53.30,
+                longitude: -3.41
+            }
+        );
+        assert_eq!(
+            LocationQuery::parse("-33.87, 151.21").unwrap(),
+            LocationQuery::Coordinates {
+                latitude: -33.87,
+                longitude: 151.21
+            }
+        );
+        assert!(LocationQuery::parse("91.0,0.0").is_err());
+        assert!(LocationQuery::parse("0.0,181.0").is_err());
+    }
+
+    #[test]
+    fn names_with_commas_are_not_coordinates() {
+        assert_eq!(
+            LocationQuery::parse("Paris, France").unwrap(),
+            LocationQuery::Name("Paris, France".into())
+        );
+    }
+
+    #[test]
+    fn parses_postcodes() {
+        for (input, expected) in [
+            ("LL18 6AA", "LL18 6AA"),
+            ("ll186aa", "LL18 6AA"),
+            ("SW1A 1AA", "SW1A 1AA"),
+            ("M1 1AE", "M1 1AE"),
+            ("B33 8TH", "B33 8TH"),
+            ("CR2 6XH", "CR2 6XH"),
+            ("LL18", "LL18"),
+            ("w1a", "W1A"),
+        ] {
+            assert_eq!(
+                LocationQuery::parse(input).unwrap(),
+                LocationQuery::Postcode(expected.into()),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn names_are_not_postcodes() {
+        for input in ["Dyserth", "Rhyl", "AT&T Park", "Zürich"] {
+            assert_eq!(
+                LocationQuery::parse(input).unwrap(),
+                LocationQuery::Name(input.into()),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn postcode_search_uses_outward_code() {
+        let url = postcode_search_url(LOCATOR_ENDPOINT, "LL18 6AA").unwrap();
+        assert_eq!(search_term(&url), "LL18");
+    }
+
+    #[test]
+    fn coordinates_search_includes_position() {
+        let url = coordinates_search_url(LOCATOR_ENDPOINT, 53.3, -3.41).unwrap();
+        assert!(url.as_str().contains("la=53.3&lo=-3.41&"));
+    }
+
+    #[test]
+    fn finds_nearest_location() {
+        let location = |id: &str, latitude: f64, longitude: f64| WeatherLocationResult {
+            id: id.into(),
+            name: id.into(),
+            container: "".into(),
+            country: "GB".into(),
+            latitude,
+            longitude,
+        };
+        let results = vec![
+            location("2643743", 51.50853, -0.12574),
+            location("2650584", 53.30032, -3.41262),
+            location("2639409", 53.31929, -3.49228),
+        ];
+
+        let mut results = results;
+        sort_by_distance(&mut results, 53.30, -3.41);
+        let ids: Vec<_> = results.iter().map(|result| result.id.as_str()).collect();
+        assert_eq!(ids, ["2650584", "2639409", "2643743"]);
+    }
+
+    #[test]
+    fn encodes_non_latin_scripts() {
+        let url = location_search_url(LOCATOR_ENDPOINT, "東京").unwrap();
+        assert!(url.as_str().contains("s=%E6%9D%B1%E4%BA%AC&"));
+        assert_eq!(search_term(&url), "東京");
+    }
+}
@@ -0,0 +1,196 @@
+use crate::astro::{self, Observer};
+use crate::client::WeatherForecastResponse;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+
+/// A forecast day's sunrise and sunset, in the location's local time as the BBC reports them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Daylight {
+    pub date: NaiveDate,
+    pub sunrise: NaiveTime,
+    pub sunset: NaiveTime,
+}
+
+impl Daylight {
+    /// The reported times for a day, or ones worked out from the location when they're missing.
+    pub fn for_day(forecast: &WeatherForecastResponse, day: usize) -> Option<Self> {
+        let report = &forecast.forecasts.get(day)?.summary.report;
+        let date = NaiveDate::parse_from_str(&report.local_date, "%Y-%m-%d").ok()?;
+        let parse =
+            |time: &Option<String>| NaiveTime::parse_from_str(time.as_deref()?, "%H:%M").ok();
+        let (sunrise, sunset) = match (parse(&report.sunrise), parse(&report.sunset)) {
+            (Some(sunrise), Some(sunset)) => (sunrise, sunset),
+            _ => Self::calculate(forecast, date)?,
+        };
+        Some(Self {
+            date,
+            sunrise,
+            sunset,
+        })
+    }
+
+    fn calculate(
+        forecast: &WeatherForecastResponse,
+        date: NaiveDate,
+    ) -> Option<(NaiveTime, NaiveTime)> {
+        let location = &forecast.location;
+        let daylight =
+            astro::sun_times(Observer::new(location.latitude, location.longitude), date).daylight;
+        let offset = offset(forecast);
+        let local = |at: DateTime<Utc>| at.with_timezone(&offset).time();
+        Some((local(daylight.rise?), local(daylight.set?)))
+    }
+
+    /// From sunrise to sunset, which is on the next day by the clock when the sun sets after
+    /// midnight, as it can far west in a timezone.
+    pub fn length(&self) -> TimeDelta {
+        let sunrise = self.date.and_time(self.sunrise);
+        let mut sunset = self.date.and_time(self.sunset);
+        if sunset < sunrise {
+            sunset += TimeDelta::days(1);
+        }
+        sunset - sunrise
+    }
+
+    /// How far through the day the sun is at a local time, from 0 at sunrise to 1 at sunset.
+    ///
+    /// `None` while the sun is down or on another day.
+    pub fn progress(&self, now: NaiveDateTime) -> Option<f64> {
+        let length = self.length().num_seconds();
+        if now.date() != self.date || length <= 0 {
+            return None;
+        }
+        let elapsed = (now.time() - self.sunrise).num_seconds();
+        (0..=length)
+            .contains(&elapsed)
+            .then(|| elapsed as f64 / length as f64)
+    }
+}
+
+/// The time at the forecast's location.
+///
+/// Forecasts are stamped with the location's UTC offset, which is all the BBC tells us of its
+/// timezone, so this drifts by an hour only when the clocks change after the forecast was issued.
+pub(crate) fn local_now(forecast: &WeatherForecastResponse, now: DateTime<Utc>) -> NaiveDateTime {
+    now.with_timezone(&offset(forecast)).naive_local()
+}
+
+/// The location's UTC offset when the forecast was issued.
+pub(crate) fn offset(forecast: &WeatherForecastResponse) -> FixedOffset {
+    forecast
+        .forecasts
+        .first()
+        .and_then(|day| DateTime::parse_from_rfc3339(&day.summary.last_updated).ok())
+        .map_or(FixedOffset::east_opt(0).unwrap(), |stamp| *stamp.offset())
+}
+
+/// Formats a length of daylight such as `13h 50m`.
+pub(crate) fn format_length(length: TimeDelta) -> String {
+    format!("{}h {:02}m", length.num_hours(), length.num_minutes() % 60)
+}
+
+/// Describes how the day's daylight compares with the day before.
+///
+/// The forecast starts today, so for the first day it compares tomorrow with today instead.
+pub(crate) fn change(forecast: &WeatherForecastResponse, day: usize) -> Option<String> {
+    let (earlier, later, label) = match day {
+        0 => (0, 1, "tomorrow"),
+        day => (day - 1, day, "on the day before"),
+    };
+    let earlier = Daylight::for_day(forecast, earlier)?;
+    let later = Daylight::for_day(forecast, later)?;
+    let minutes = (later.length() - earlier.length()).num_minutes();
+    let sign = match minutes {
+        0 => "±",
+        minutes if minutes > 0 => "+",
+        _ => "-",
+    };
+    Some(format!("{}{}m {}", sign, minutes.abs(), label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use chrono::TimeZone;
+
+    #[test]
+    fn reads_the_days_sunrise_and_sunset() {
+        let forecast = test_forecast();
+        let today = Daylight::for_day(&forecast, 0).unwrap();
+        assert_eq!(today.date, NaiveDate::from_ymd_opt(2024, 8, 29).unwrap());
+        assert_eq!(today.sunrise, NaiveTime::from_hms_opt(6, 19, 0).unwrap());
+        assert_eq!(today.sunset, NaiveTime::from_hms_opt(20, 9, 0).unwrap());
+        assert_eq!(format_length(today.length()), "13h 50m");
+        assert_eq!(Daylight::for_day(&forecast, 14), None);
+    }
+
+    #[test]
+    fn works_out_missing_times() {
+        let mut forecast = test_forecast();
+        forecast.forecasts[0].summary.report.sunrise = None;
+        let today = Daylight::for_day(&forecast, 0).unwrap();
+        // The BBC has 06:19 and 20:09
+        let minutes = |time: NaiveTime| (time - NaiveTime::MIN).num_minutes();
+        assert!((minutes(today.sunrise) - (6 * 60 + 19)).abs() <= 1);
+        assert!((minutes(today.sunset) - (20 * 60 + 9)).abs() <= 1);
+    }
+
+    #[test]
+    fn counts_daylight_past_midnight() {
+        let late = Daylight {
+            date: NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(),
+            sunrise: NaiveTime::from_hms_opt(8, 10, 0).unwrap(),
+            sunset: NaiveTime::from_hms_opt(0, 30, 0).unwrap(),
+        };
+        assert_eq!(format_length(late.length()), "16h 20m");
+    }
+
+    #[test]
+    fn follows_the_sun_across_the_day() {
+        let today = Daylight::for_day(&test_forecast(), 0).unwrap();
+        let at = |hour, minute| today.date.and_hms_opt(hour, minute, 0).unwrap();
+        assert_eq!(today.progress(at(6, 19)), Some(0.0));
+        assert_eq!(today.progress(at(13, 14)), Some(0.5));
+        assert_eq!(today.progress(at(20, 9)), Some(1.0));
+        assert_eq!(today.progress(at(5, 0)), None);
+        assert_eq!(today.progress(at(22, 0)), None);
+
+        let tomorrow = today
+            .date
+            .succ_opt()
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        assert_eq!(today.progress(tomorrow), None);
+    }
+
+    #[test]
+    fn uses_the_locations_offset() {
+        let now = Utc.with_ymd_and_hms(2024, 8, 29, 23, 30, 0).unwrap();
+        let local = local_now(&test_forecast(), now);
+        assert_eq!(
+            local,
+            NaiveDate::from_ymd_opt(2024, 8, 30)
+                .unwrap()
+                .and_hms_opt(0, 30, 0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn compares_with_the_neighbouring_day() {
+        let forecast = test_forecast();
+        // 13h 50m today and 13h 45m tomorrow
+        assert_eq!(change(&forecast, 0).as_deref(), Some("-5m tomorrow"));
+        assert_eq!(
+            change(&forecast, 1).as_deref(),
+            Some("-5m on the day before")
+        );
+        // 13h 42m then 13h 38m
+        assert_eq!(
+            change(&forecast, 3).as_deref(),
+            Some("-4m on the day before")
+        );
+        assert_eq!(change(&forecast, 14), None);
+    }
+}
@@ -0,0 +1,93 @@
+//! A local stand-in for the BBC locator and forecast services, for tests that go over HTTP.
+
+use crate::client::WeatherAPI;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The only location the stand-in knows, Dyserth as in the test forecast.
+pub(crate) const LOCATION_ID: &str = "2650584";
+
+/// A numeric postcode the locator knows, which isn't a BBC location id.
+pub(crate) const NUMERIC_POSTCODE: &str = "18018";
+
+/// How long the stand-in takes to answer a forecast request.
+pub(crate) const FORECAST_DELAY: Duration = Duration::from_millis(200);
+
+/// Counts the requests made of the stand-in.
+#[derive(Default)]
+pub(crate) struct Upstream {
+    pub locator_requests: AtomicUsize,
+    pub forecast_requests: AtomicUsize,
+}
+
+async fn locator(
+    State(upstream): State<Arc<Upstream>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    upstream.locator_requests.fetch_add(1, Ordering::SeqCst);
+    let results = match params.get("s").map(String::as_str) {
+        Some("Dyserth") | Some(NUMERIC_POSTCODE) => json!([{
+            "id": LOCATION_ID,
+            "name": "Dyserth",
+            "container": "Denbighshire",
+            "country": "GB",
+            "latitude": 53.30032,
+            "longitude": -3.41262
+        }]),
+        _ => json!([]),
+    };
+    let total = results.as_array().unwrap().len();
+    Json(json!({
+        "response": { "results": { "results": results, "totalResults": total } }
+    }))
+    .into_response()
+}
+
+async fn forecast(State(upstream): State<Arc<Upstream>>, Path(id): Path<String>) -> Response {
+    upstream.forecast_requests.fetch_add(1, Ordering::SeqCst);
+    // Slow enough for concurrent requests to overlap
+    tokio::time::sleep(FORECAST_DELAY).await;
+    match id.as_str() {
+        LOCATION_ID => std::fs::read_to_string("src/test_data/weather_forecast.json")
+            .unwrap()
+            .into_response(),
+        // An id the upstream itself fails on
+        "500" => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        _ => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Serves an app on a free local port, returning its address.
+pub(crate) async fn spawn(app: Router) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    address
+}
+
+/// Starts the stand-in, returning a client pointed at it and its request counts.
+pub(crate) async fn start() -> (WeatherAPI, Arc<Upstream>) {
+    let upstream = Arc::new(Upstream::default());
+    let address = spawn(
+        Router::new()
+            .route("/locator", get(locator))
+            .route("/forecast/:id", get(forecast))
+            .with_state(upstream.clone()),
+    )
+    .await;
+
+    let weather_api = WeatherAPI::new().with_endpoints(
+        &format!("http://{}/locator", address),
+        &format!("http://{}/forecast", address),
+    );
+    (weather_api, upstream)
+}
@@ -0,0 +1,163 @@
+use crate::client::WeatherDetailedReport;
+use serde::{Deserialize, Deserializer};
+
+/// How far one can see, in the Met Office's words from best to worst.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum Visibility {
+    Excellent,
+    VeryGood,
+    Good,
+    Moderate,
+    Poor,
+    VeryPoor,
+}
+
+impl Visibility {
+    /// Parses a visibility such as `Good` or `Very Poor`, ignoring case and separators.
+    pub fn parse(visibility: &str) -> Option<Self> {
+        let visibility = visibility
+            .trim()
+            .to_ascii_uppercase()
+            .replace([' ', '-'], "_");
+        match visibility.as_str() {
+            "EXCELLENT" => Some(Visibility::Excellent),
+            "VERY_GOOD" => Some(Visibility::VeryGood),
+            "GOOD" => Some(Visibility::Good),
+            "MODERATE" => Some(Visibility::Moderate),
+            "POOR" => Some(Visibility::Poor),
+            "VERY_POOR" => Some(Visibility::VeryPoor),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Visibility::Excellent => "Excellent",
+            Visibility::VeryGood => "Very Good",
+            Visibility::Good => "Good",
+            Visibility::Moderate => "Moderate",
+            Visibility::Poor => "Poor",
+            Visibility::VeryPoor => "Very Poor",
+        }
+    }
+
+    /// Fits a column of the hourly table.
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Visibility::Excellent => "Exc",
+            Visibility::VeryGood => "V Good",
+            Visibility::Good => "Good",
+            Visibility::Moderate => "Mod",
+            Visibility::Poor => "Poor",
+            Visibility::VeryPoor => "V Poor",
+        }
+    }
+
+    /// Under 4km, when fog or mist makes driving and cycling hazardous.
+    pub fn is_poor(self) -> bool {
+        self >= Visibility::Poor
+    }
+
+    pub fn colour(self) -> &'static str {
+        match self {
+            Visibility::Excellent | Visibility::VeryGood | Visibility::Good => "green",
+            Visibility::Moderate => "yellow",
+            Visibility::Poor => "red",
+            Visibility::VeryPoor => "magenta",
+        }
+    }
+}
+
+/// Reads the visibility of an hourly report, leaving out any the BBC adds that we don't know.
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Visibility>, D::Error> {
+    let visibility = Option::<String>::deserialize(deserializer)?;
+    Ok(visibility.as_deref().and_then(Visibility::parse))
+}
+
+/// The worst visibility of a day and when it first gets poor, if it does.
+#[derive(Debug, PartialEq)]
+pub(crate) struct DayVisibility<'a> {
+    pub worst: Visibility,
+    /// The timeslot of the first poor hour
+    pub poor_from: Option<&'a str>,
+}
+
+impl<'a> DayVisibility<'a> {
+    pub fn new(reports: &'a [WeatherDetailedReport]) -> Option<Self> {
+        let worst = reports
+            .iter()
+            .filter_map(|report| report.visibility)
+            .max()?;
+        let poor_from = reports
+            .iter()
+            .find(|report| report.visibility.is_some_and(Visibility::is_poor))
+            .map(|report| report.timeslot.as_str());
+        Some(Self { worst, poor_from })
+    }
+
+    /// Such as `Visibility Good` or `Very Poor visibility from 07:00`.
+    pub fn describe(&self) -> String {
+        match self.poor_from {
+            Some(timeslot) => format!("{} visibility from {}", self.worst.name(), timeslot),
+            None => format!("Visibility {}", self.worst.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+
+    #[test]
+    fn parses_visibilities() {
+        assert_eq!(Visibility::parse("Good"), Some(Visibility::Good));
+        assert_eq!(Visibility::parse("Very Poor"), Some(Visibility::VeryPoor));
+        assert_eq!(Visibility::parse("VERY_GOOD"), Some(Visibility::VeryGood));
+        assert_eq!(Visibility::parse(" moderate "), Some(Visibility::Moderate));
+        assert_eq!(Visibility::parse("Murky"), None);
+    }
+
+    #[test]
+    fn reads_every_hours_visibility() {
+        let forecast = test_forecast();
+        let reports = &forecast.forecasts[1].detailed.reports;
+        assert_eq!(reports[0].visibility, Some(Visibility::Moderate));
+        assert_eq!(reports[1].visibility, Some(Visibility::VeryPoor));
+        assert!(forecast
+            .forecasts
+            .iter()
+            .flat_map(|day| &day.detailed.reports)
+            .all(|report| report.visibility.is_some()));
+    }
+
+    #[test]
+    fn only_poor_visibility_is_poor() {
+        assert!(!Visibility::Good.is_poor());
+        assert!(!Visibility::Moderate.is_poor());
+        assert!(Visibility::Poor.is_poor());
+        assert!(Visibility::VeryPoor.is_poor());
+    }
+
+    #[test]
+    fn badges_the_day() {
+        let forecast = test_forecast();
+        let today = DayVisibility::new(&forecast.forecasts[0].detailed.reports).unwrap();
+        // Mist overnight
+        assert_eq!(today.describe(), "Visibility Moderate");
+
+        let tomorrow = DayVisibility::new(&forecast.forecasts[1].detailed.reports).unwrap();
+        assert_eq!(
+            tomorrow,
+            DayVisibility {
+                worst: Visibility::VeryPoor,
+                poor_from: Some("07:00"),
+            }
+        );
+        assert_eq!(tomorrow.describe(), "Very Poor visibility from 07:00");
+
+        assert_eq!(DayVisibility::new(&[]), None);
+    }
+}
@@ -0,0 +1,166 @@
+/// The 16 points of the compass the BBC reports wind directions with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum WindDirection {
+    North,
+    NorthNorthEast,
+    NorthEast,
+    EastNorthEast,
+    East,
+    EastSouthEast,
+    SouthEast,
+    SouthSouthEast,
+    South,
+    SouthSouthWest,
+    SouthWest,
+    WestSouthWest,
+    West,
+    WestNorthWest,
+    NorthWest,
+    NorthNorthWest,
+}
+
+pub(crate) const POINTS: [WindDirection; 16] = [
+    WindDirection::North,
+    WindDirection::NorthNorthEast,
+    WindDirection::NorthEast,
+    WindDirection::EastNorthEast,
+    WindDirection::East,
+    WindDirection::EastSouthEast,
+    WindDirection::SouthEast,
+    WindDirection::SouthSouthEast,
+    WindDirection::South,
+    WindDirection::SouthSouthWest,
+    WindDirection::SouthWest,
+    WindDirection::WestSouthWest,
+    WindDirection::West,
+    WindDirection::WestNorthWest,
+    WindDirection::NorthWest,
+    WindDirection::NorthNorthWest,
+];
+
+const ABBREVIATIONS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+impl WindDirection {
+    /// Parses a BBC abbreviation such as `WSW`.
+    pub fn parse(abbreviation: &str) -> Option<Self> {
+        let abbreviation = abbreviation.trim();
+        ABBREVIATIONS
+            .iter()
+            .position(|candidate| candidate.eq_ignore_ascii_case(abbreviation))
+            .map(|index| POINTS[index])
+    }
+
+    fn index(self) -> usize {
+        POINTS.iter().position(|point| *point == self).unwrap()
+    }
+
+    /// The bearing the wind blows from, clockwise from north.
+    pub fn degrees(self) -> f64 {
+        self.index() as f64 * 22.5
+    }
+
+    pub fn abbreviation(self) -> &'static str {
+        ABBREVIATIONS[self.index()]
+    }
+
+    /// The bearing the wind blows towards, which is where a wind arrow points.
+    pub fn downwind_degrees(self) -> f64 {
+        (self.degrees() + 180.0) % 360.0
+    }
+
+    /// The arrow closest to the downwind bearing, as only 8 directions have one.
+    pub fn arrow(self) -> char {
+        const ARROWS: [char; 8] = ['↑', '↗', '→', '↘', '↓', '↙', '←', '↖'];
+        let eighth = (self.downwind_degrees() / 45.0).round() as usize % ARROWS.len();
+        ARROWS[eighth]
+    }
+}
+
+/// Upper bounds in kph of Beaufort forces 0 to 11, anything faster is force 12.
+const BEAUFORT_LIMITS: [f64; 12] = [
+    1.0, 6.0, 12.0, 20.0, 29.0, 39.0, 50.0, 62.0, 75.0, 89.0, 103.0, 118.0,
+];
+
+const BEAUFORT_DESCRIPTIONS: [&str; 13] = [
+    "Calm",
+    "Light air",
+    "Light breeze",
+    "Gentle breeze",
+    "Moderate breeze",
+    "Fresh breeze",
+    "Strong breeze",
+    "Near gale",
+    "Gale",
+    "Strong gale",
+    "Storm",
+    "Violent storm",
+    "Hurricane force",
+];
+
+/// The Beaufort force of a wind speed, with its description.
+pub(crate) fn beaufort(kph: f64) -> (u8, &'static str) {
+    let force = BEAUFORT_LIMITS
+        .iter()
+        .position(|limit| kph < *limit)
+        .unwrap_or(BEAUFORT_LIMITS.len());
+    (force as u8, BEAUFORT_DESCRIPTIONS[force])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_abbreviation() {
+        for (index, abbreviation) in ABBREVIATIONS.iter().enumerate() {
+            let direction = WindDirection::parse(abbreviation).unwrap();
+            assert_eq!(direction, POINTS[index]);
+            assert_eq!(direction.abbreviation(), *abbreviation);
+            assert_eq!(direction.degrees(), index as f64 * 22.5);
+        }
+        assert_eq!(
+            WindDirection::parse("wsw"),
+            Some(WindDirection::WestSouthWest)
+        );
+        assert_eq!(WindDirection::parse("X"), None);
+    }
+
+    #[test]
+    fn parses_every_direction_in_a_forecast() {
+        let forecast = crate::client::test_forecast();
+
+        for day in &forecast.forecasts {
+            let summary = &day.summary.report.wind_direction;
+            assert!(WindDirection::parse(summary).is_some(), "{}", summary);
+            for report in &day.detailed.reports {
+                let direction = &report.wind_direction;
+                assert!(WindDirection::parse(direction).is_some(), "{}", direction);
+            }
+        }
+    }
+
+    #[test]
+    fn arrows_point_downwind() {
+        assert_eq!(WindDirection::North.arrow(), '↓');
+        assert_eq!(WindDirection::West.arrow(), '→');
+        assert_eq!(WindDirection::SouthWest.arrow(), '↗');
+        assert_eq!(WindDirection::SouthSouthWest.downwind_degrees(), 22.5);
+        // Halfway between two arrows rounds clockwise
+        assert_eq!(WindDirection::SouthSouthWest.arrow(), '↗');
+        assert_eq!(WindDirection::NorthNorthEast.arrow(), '↙');
+    }
+
+    #[test]
+    fn grades_the_beaufort_scale() {
+        assert_eq!(beaufort(0.0), (0, "Calm"));
+        assert_eq!(beaufort(5.9), (1, "Light air"));
+        assert_eq!(beaufort(6.0), (2, "Light breeze"));
+        assert_eq!(beaufort(19.0), (3, "Gentle breeze"));
+        assert_eq!(beaufort(62.0), (8, "Gale"));
+        assert_eq!(beaufort(117.9), (11, "Violent storm"));
+        assert_eq!(beaufort(150.0), (12, "Hurricane force"));
+    }
+}
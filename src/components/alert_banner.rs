@@ -0,0 +1,73 @@
+use anathema::component::{Component, ComponentId, Elements, Emitter, State, Value};
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+
+struct AlertBanner;
+
+impl AlertBanner {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl Component for AlertBanner {
+    type State = AlertBannerState;
+    type Message = AlertBannerMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        state.visible.set(!message.text.is_empty());
+        state.text.set(message.text);
+        state.colour.set(message.colour);
+    }
+}
+
+#[derive(State)]
+struct AlertBannerState {
+    visible: Value<bool>,
+    text: Value<String>,
+    colour: Value<String>,
+}
+
+impl AlertBannerState {
+    fn new() -> Self {
+        Self {
+            visible: Value::new(false),
+            text: Value::new("".to_string()),
+            colour: Value::new("yellow".to_string()),
+        }
+    }
+}
+
+pub struct AlertBannerMessage {
+    text: String,
+    colour: String,
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<AlertBannerMessage> {
+    runtime
+        .register_component(
+            "alertBanner",
+            "src/templates/alert_banner.aml",
+            AlertBanner::new(),
+            AlertBannerState::new(),
+        )
+        .unwrap()
+}
+
+/// Shows `text` in `colour`, or hides the banner when `text` is empty.
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<AlertBannerMessage>,
+    text: String,
+    colour: String,
+) {
+    let _ = emitter.emit(id, AlertBannerMessage { text, colour });
+}
@@ -0,0 +1,114 @@
+use anathema::component::{Component, ComponentId, Elements, Emitter, State, Value};
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+use chrono::Utc;
+
+use crate::client::pressure::{Barometer, Tendency};
+use crate::client::WeatherForecastResponse;
+use crate::components::spark::sparkline;
+
+struct BarometerComponent;
+
+impl BarometerComponent {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl Component for BarometerComponent {
+    type State = BarometerState;
+    type Message = BarometerMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        state.pressure.set(message.pressure);
+        state.reading.set(message.reading);
+        state.past.set(message.past);
+        state.coming.set(message.coming);
+        state.sparkline.set(message.sparkline);
+    }
+}
+
+#[derive(State)]
+struct BarometerState {
+    pressure: Value<String>,
+    reading: Value<String>,
+    past: Value<String>,
+    coming: Value<String>,
+    sparkline: Value<String>,
+}
+
+impl BarometerState {
+    fn new() -> Self {
+        Self {
+            pressure: Value::new("".into()),
+            reading: Value::new("".into()),
+            past: Value::new("".into()),
+            coming: Value::new("".into()),
+            sparkline: Value::new("".into()),
+        }
+    }
+}
+
+pub(crate) struct BarometerMessage {
+    pressure: String,
+    reading: String,
+    past: String,
+    coming: String,
+    sparkline: String,
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<BarometerMessage> {
+    runtime
+        .register_component(
+            "barometer",
+            "src/templates/barometer.aml",
+            BarometerComponent::new(),
+            BarometerState::new(),
+        )
+        .unwrap()
+}
+
+/// Shows the pressure at the location's current hour, how it is changing and the day ahead.
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<BarometerMessage>,
+    forecast: &WeatherForecastResponse,
+) {
+    let message = match Barometer::read(forecast, Utc::now()) {
+        Some(barometer) => {
+            let describe = |tendency: Option<Tendency>| {
+                tendency.map_or("--".to_string(), |tendency| tendency.describe())
+            };
+            // Points the way the pressure is heading, as far as the forecast shows
+            let pressure = match barometer.tendency() {
+                Some(tendency) => {
+                    format!("{:.0} mb {}", barometer.pressure, tendency.trend.arrow())
+                }
+                None => format!("{:.0} mb", barometer.pressure),
+            };
+            BarometerMessage {
+                pressure,
+                reading: barometer.reading.name().to_string(),
+                past: describe(barometer.past),
+                coming: describe(barometer.coming),
+                sparkline: sparkline(&barometer.hourly),
+            }
+        }
+        None => BarometerMessage {
+            pressure: "Not forecast".to_string(),
+            reading: "".to_string(),
+            past: "--".to_string(),
+            coming: "--".to_string(),
+            sparkline: "".to_string(),
+        },
+    };
+    let _ = emitter.emit(id, message);
+}
@@ -0,0 +1,119 @@
+use anathema::component::{Component, ComponentId, Elements, Emitter, State, Value};
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+
+use crate::client::{Units, WeatherDetailedReport};
+
+struct CurrentConditions;
+
+impl CurrentConditions {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl Component for CurrentConditions {
+    type State = CurrentConditionsState;
+    type Message = CurrentConditionsMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        state.temperature.set(message.temperature);
+        state.feels_like.set(message.feels_like);
+        state.humidity.set(message.humidity);
+        state.dew_point.set(message.dew_point);
+        state.heat_index.set(message.heat_index);
+        state.wind_chill.set(message.wind_chill);
+        state.humidex.set(message.humidex);
+        state.apparent_temperature.set(message.apparent_temperature);
+    }
+}
+
+#[derive(State)]
+struct CurrentConditionsState {
+    temperature: Value<String>,
+    feels_like: Value<String>,
+    humidity: Value<String>,
+    dew_point: Value<String>,
+    heat_index: Value<String>,
+    wind_chill: Value<String>,
+    humidex: Value<String>,
+    apparent_temperature: Value<String>,
+}
+
+impl CurrentConditionsState {
+    fn new() -> Self {
+        Self {
+            temperature: Value::new("".into()),
+            feels_like: Value::new("".into()),
+            humidity: Value::new("".into()),
+            dew_point: Value::new("".into()),
+            heat_index: Value::new("".into()),
+            wind_chill: Value::new("".into()),
+            humidex: Value::new("".into()),
+            apparent_temperature: Value::new("".into()),
+        }
+    }
+}
+
+pub(crate) struct CurrentConditionsMessage {
+    temperature: String,
+    feels_like: String,
+    humidity: String,
+    dew_point: String,
+    heat_index: String,
+    wind_chill: String,
+    humidex: String,
+    apparent_temperature: String,
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<CurrentConditionsMessage> {
+    runtime
+        .register_component(
+            "currentConditions",
+            "src/templates/current_conditions.aml",
+            CurrentConditions::new(),
+            CurrentConditionsState::new(),
+        )
+        .unwrap()
+}
+
+/// Shows the conditions in the hour a report covers, the hour under way when the forecast arrives.
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<CurrentConditionsMessage>,
+    report: &WeatherDetailedReport,
+    units: Units,
+) {
+    let temperature = |celsius| {
+        format!(
+            "{:.0}°{}",
+            units.temperature(celsius),
+            units.temperature_symbol()
+        )
+    };
+    let _ = emitter.emit(
+        id,
+        CurrentConditionsMessage {
+            temperature: temperature(report.temperature_c),
+            feels_like: temperature(report.feels_like_c()),
+            humidity: report
+                .humidity
+                .map_or("--".to_string(), |humidity| format!("{:.0}%", humidity)),
+            dew_point: report.dew_point_c().map_or("--".to_string(), temperature),
+            heat_index: report.heat_index_c().map_or("--".to_string(), temperature),
+            wind_chill: report.wind_chill_c().map_or("--".to_string(), temperature),
+            humidex: report.humidex_c().map_or("--".to_string(), temperature),
+            apparent_temperature: report
+                .apparent_temperature_c()
+                .map_or("--".to_string(), temperature),
+        },
+    );
+}
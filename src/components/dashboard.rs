@@ -0,0 +1,250 @@
+use anathema::component::{Component, ComponentId, Elements, Emitter, List, State, Value};
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+use chrono::{DateTime, Utc};
+
+use crate::client::{LocationForecast, Units, WeatherType};
+use crate::components::graph_component::SERIES_COLOURS;
+
+struct Dashboard;
+
+impl Dashboard {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl Component for Dashboard {
+    type State = DashboardState;
+    type Message = DashboardMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        while state.cards.len() > 0 {
+            state.cards.pop_back();
+        }
+        let now = Utc::now();
+        for (index, location) in message.locations.iter().enumerate() {
+            let (_, colour) = SERIES_COLOURS[index % SERIES_COLOURS.len()];
+            state
+                .cards
+                .push_back(Card::new(location, colour, message.units, now));
+        }
+    }
+}
+
+/// A compact summary of one location.
+#[derive(State)]
+struct Card {
+    name: Value<String>,
+    icon: Value<String>,
+    weather: Value<String>,
+    temperature: Value<String>,
+    range: Value<String>,
+    wind: Value<String>,
+    rain: Value<String>,
+    /// Matches the location's series on the graph
+    colour: Value<String>,
+}
+
+impl Card {
+    fn new(location: &LocationForecast, colour: &str, units: Units, now: DateTime<Utc>) -> Self {
+        let text = CardText::new(location, units, now);
+        Self {
+            name: Value::new(text.name),
+            icon: Value::new(text.icon),
+            weather: Value::new(text.weather),
+            temperature: Value::new(text.temperature),
+            range: Value::new(text.range),
+            wind: Value::new(text.wind),
+            rain: Value::new(text.rain),
+            colour: Value::new(colour.to_string()),
+        }
+    }
+}
+
+/// What a card says about its location.
+#[derive(Debug, Default, PartialEq)]
+struct CardText {
+    name: String,
+    icon: String,
+    weather: String,
+    temperature: String,
+    range: String,
+    wind: String,
+    rain: String,
+}
+
+impl CardText {
+    fn new(location: &LocationForecast, units: Units, now: DateTime<Utc>) -> Self {
+        let forecast = match &location.forecast {
+            Ok(forecast) => forecast,
+            Err(reason) => {
+                return Self {
+                    name: location.location.clone(),
+                    icon: WeatherType::Unknown.icon().to_string(),
+                    weather: reason.clone(),
+                    ..Self::default()
+                }
+            }
+        };
+        let Some(today) = forecast.forecasts.first() else {
+            return Self {
+                name: forecast.location.name.clone(),
+                icon: WeatherType::Unknown.icon().to_string(),
+                weather: "No forecast".to_string(),
+                ..Self::default()
+            };
+        };
+
+        let summary = &today.summary.report;
+        let current = forecast.current_report(now);
+        let symbol = units.temperature_symbol();
+        let temperature = current
+            .map(|report| format!("{:.0}°{}", units.temperature(report.temperature_c), symbol))
+            .unwrap_or_default();
+        let weather_type = current.map_or(summary.weather_type, |report| report.weather_type);
+        let weather_text = current.map_or(&summary.weather_type_text, |report| {
+            &report.weather_type_text
+        });
+
+        Self {
+            name: forecast.location.name.clone(),
+            icon: WeatherType::from_code(weather_type).icon().to_string(),
+            weather: weather_text.clone(),
+            temperature,
+            range: format!(
+                "{:.0}°/{:.0}°{}",
+                units.temperature(summary.min_temp_c),
+                units.temperature(summary.max_temp_c),
+                symbol
+            ),
+            wind: format!(
+                "{:.0} {} {}",
+                units.speed(summary.wind_speed_kph),
+                units.speed_symbol(),
+                summary.wind_direction
+            ),
+            rain: format!("{:.0}%", summary.precipitation_probability_in_percent),
+        }
+    }
+}
+
+#[derive(State)]
+struct DashboardState {
+    cards: Value<List<Card>>,
+}
+
+impl DashboardState {
+    fn new() -> Self {
+        Self {
+            cards: List::from_iter(vec![]),
+        }
+    }
+}
+
+pub struct DashboardMessage {
+    locations: Vec<LocationForecast>,
+    units: Units,
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<DashboardMessage> {
+    runtime
+        .register_component(
+            "dashboard",
+            "src/templates/dashboard.aml",
+            Dashboard::new(),
+            DashboardState::new(),
+        )
+        .unwrap()
+}
+
+/// Shows a card per location, coloured like its series on the graph.
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<DashboardMessage>,
+    locations: Vec<LocationForecast>,
+    units: Units,
+) {
+    let _ = emitter.emit(id, DashboardMessage { locations, units });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use chrono::TimeZone;
+
+    /// 15:00 at the location, the first hour of the forecast.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 8, 29, 14, 0, 0).unwrap()
+    }
+
+    fn fetched() -> LocationForecast {
+        LocationForecast {
+            location: "Dyserth".to_string(),
+            forecast: Ok(test_forecast()),
+        }
+    }
+
+    #[test]
+    fn sums_up_a_location() {
+        assert_eq!(
+            CardText::new(&fetched(), Units::Metric, now()),
+            CardText {
+                name: "Dyserth".to_string(),
+                icon: WeatherType::PartlyCloudy.icon().to_string(),
+                weather: "Sunny Intervals".to_string(),
+                temperature: "18°C".to_string(),
+                range: "9°/18°C".to_string(),
+                wind: "19 kph W".to_string(),
+                rain: "0%".to_string(),
+            }
+        );
+
+        let imperial = CardText::new(&fetched(), Units::Imperial, now());
+        assert_eq!(imperial.temperature, "64°F");
+        assert_eq!(imperial.range, "48°/64°F");
+        assert_eq!(imperial.wind, "12 mph W");
+
+        // The current hour moves on through the day
+        let evening = Utc.with_ymd_and_hms(2024, 8, 29, 20, 0, 0).unwrap();
+        assert_eq!(
+            CardText::new(&fetched(), Units::Metric, evening).temperature,
+            "14°C"
+        );
+    }
+
+    #[test]
+    fn says_why_a_location_is_missing() {
+        let failed = LocationForecast {
+            location: "Nowhere".to_string(),
+            forecast: Err("No results found".to_string()),
+        };
+        assert_eq!(
+            CardText::new(&failed, Units::Metric, now()),
+            CardText {
+                name: "Nowhere".to_string(),
+                icon: WeatherType::Unknown.icon().to_string(),
+                weather: "No results found".to_string(),
+                ..CardText::default()
+            }
+        );
+
+        let mut empty = fetched();
+        if let Ok(forecast) = &mut empty.forecast {
+            forecast.forecasts.clear();
+        }
+        assert_eq!(
+            CardText::new(&empty, Units::Metric, now()).weather,
+            "No forecast"
+        );
+    }
+}
@@ -1,10 +1,15 @@
 use anathema::backend::tui::Style;
 use anathema::component::{Color, Component, ComponentId, Elements, Emitter, List, State, Value};
-use anathema::default_widgets::{Canvas};
+use anathema::default_widgets::Canvas;
 use anathema::geometry::LocalPos;
 use anathema::prelude::*;
 use anathema::runtime::RuntimeBuilder;
 
+use crate::client::{LocationForecast, Units, WeatherForecastResponse};
+
+/// Fills the likely range around each point, drawn under the points themselves.
+const BAND: char = '░';
+
 #[derive(Default)]
 struct GraphComponent;
 
@@ -27,6 +32,29 @@ impl GraphComponent {
         }
     }
 
+    fn populate_bands(
+        &mut self,
+        canvas: &mut Canvas,
+        point_width: u16,
+        bands: &[Option<(u16, u16)>],
+        min: &u16,
+        max: &u16,
+        style: &Style,
+    ) {
+        for (pt_idx, band) in bands.iter().enumerate() {
+            let Some((lower, upper)) = band else {
+                continue;
+            };
+            for value in *lower..=*upper {
+                let y = (max - min) - (value - min);
+                for width_idx in 0..point_width {
+                    let x = (pt_idx as u16 * point_width) + width_idx;
+                    canvas.put(BAND, *style, LocalPos::new(x, y));
+                }
+            }
+        }
+    }
+
     fn find_range(points: &[u16]) -> (u16, u16) {
         let min = points.iter().min().unwrap();
         let max = points.iter().max().unwrap();
@@ -45,20 +73,47 @@ impl Component for GraphComponent {
         mut elements: Elements<'_, '_>,
         _context: Context<'_, Self::State>,
     ) {
-        let range1 = Self::find_range(&message.max_temp_points);
-        let range2 = Self::find_range(&message.min_temp_points);
+        let series = match message {
+            GraphComponentMessage::Forecast {
+                max_temp_points,
+                min_temp_points,
+                max_temp_bands,
+                min_temp_bands,
+            } => vec![
+                Series {
+                    name: None,
+                    points: max_temp_points,
+                    bands: max_temp_bands,
+                    colour: Color::Red,
+                },
+                Series {
+                    name: None,
+                    points: min_temp_points,
+                    bands: min_temp_bands,
+                    colour: Color::Blue,
+                },
+            ],
+            GraphComponentMessage::Overlay(series) => series,
+        };
+
+        elements.by_tag("canvas").first(|el, _| {
+            el.to::<Canvas>().clear();
+        });
+
+        let all_points: Vec<u16> = series
+            .iter()
+            .flat_map(|series| {
+                let bands = series.bands.iter().flatten();
+                let bounds = bands.flat_map(|(lower, upper)| [*lower, *upper]);
+                series.points.iter().copied().chain(bounds)
+            })
+            .collect();
+        if all_points.is_empty() {
+            return;
+        }
 
         // Find the range of the data points
-        let min = if range1.0 > range2.0 {
-            range2.0
-        } else {
-            range1.0
-        };
-        let max = if range1.1 > range2.1 {
-            range1.1
-        } else {
-            range2.1
-        };
+        let (min, max) = Self::find_range(&all_points);
 
         state.max_temp.set(max);
         state.min_temp.set(min);
@@ -66,28 +121,51 @@ impl Component for GraphComponent {
         let range = if max - min < 10 { 10 } else { max - min };
         state.height.set(range);
 
+        let longest = series
+            .iter()
+            .map(|series| series.points.len())
+            .max()
+            .unwrap_or_default();
         let point_width = state.point_width.to_ref();
-        let width = if (message.max_temp_points.len()) < 10 {
-            10
-        } else {
-            message.max_temp_points.len() as u16
-        } * *point_width;
+        let width = if longest < 10 { 10 } else { longest as u16 } * *point_width;
         state.width.set(width);
 
-        // Populate max temps in the forecast
-        let mut style = Style::new();
-        style.set_fg(Color::Red);
         elements.by_tag("canvas").first(|el, _| {
             let canvas = el.to::<Canvas>();
-            self.populate_graph(canvas, *point_width, &message.max_temp_points, &min, &max, &style);
-        });
+            // Bands first so that neither series' band covers the other's points
+            for series in &series {
+                let mut style = Style::new();
+                style.set_fg(series.colour);
+                self.populate_bands(canvas, *point_width, &series.bands, &min, &max, &style);
+            }
+            for series in &series {
+                let mut style = Style::new();
+                style.set_fg(series.colour);
+                self.populate_graph(canvas, *point_width, &series.points, &min, &max, &style);
+            }
 
-        // Populate min temps in the forecast
-        let mut style = Style::new();
-        style.set_fg(Color::Blue);
-        elements.by_tag("canvas").first(|el, _| {
-            let canvas = el.to::<Canvas>();
-            self.populate_graph(canvas, *point_width, &message.min_temp_points, &min, &max, &style);
+            // Explain the shading, then name each overlaid series below the graph in its colour
+            let mut x = 0;
+            if series
+                .iter()
+                .any(|series| series.bands.iter().any(Option::is_some))
+            {
+                for c in format!("{} likely range  ", BAND).chars() {
+                    canvas.put(c, Style::new(), LocalPos::new(x, range + 2));
+                    x += 1;
+                }
+            }
+            for series in &series {
+                let Some(name) = &series.name else {
+                    continue;
+                };
+                let mut style = Style::new();
+                style.set_fg(series.colour);
+                for c in format!("* {}  ", name).chars() {
+                    canvas.put(c, style, LocalPos::new(x, range + 2));
+                    x += 1;
+                }
+            }
         });
     }
 }
@@ -122,9 +200,35 @@ impl GraphComponentState {
     }
 }
 
-pub struct GraphComponentMessage {
-    max_temp_points: Vec<u16>,
-    min_temp_points: Vec<u16>,
+/// The colours given to overlaid series, by position, as used by the canvas and in templates.
+pub(crate) const SERIES_COLOURS: [(Color, &str); 6] = [
+    (Color::Red, "red"),
+    (Color::Green, "green"),
+    (Color::Yellow, "yellow"),
+    (Color::Cyan, "cyan"),
+    (Color::Magenta, "magenta"),
+    (Color::Blue, "blue"),
+];
+
+pub(crate) struct Series {
+    /// Shown in the legend, the forecast's own series don't need one
+    pub name: Option<String>,
+    pub points: Vec<u16>,
+    /// The range each point is likely to fall in, where the forecast gives one
+    pub bands: Vec<Option<(u16, u16)>>,
+    pub colour: Color,
+}
+
+pub enum GraphComponentMessage {
+    /// Max and min temperatures of a single forecast, with the range each is likely to fall in
+    Forecast {
+        max_temp_points: Vec<u16>,
+        min_temp_points: Vec<u16>,
+        max_temp_bands: Vec<Option<(u16, u16)>>,
+        min_temp_bands: Vec<Option<(u16, u16)>>,
+    },
+    /// Several named series drawn together
+    Overlay(Vec<Series>),
 }
 
 pub fn create_component(
@@ -140,17 +244,121 @@ pub fn create_component(
         .unwrap()
 }
 
+/// Plots the daily max and min temperatures, shading the range each is likely to fall in.
 pub(crate) fn update_component(
     emitter: &Emitter,
     id: ComponentId<GraphComponentMessage>,
-    max_temp_points: Vec<u16>,
-    min_temp_points: Vec<u16>,
+    forecast: &WeatherForecastResponse,
+    units: Units,
 ) {
+    let reports = forecast
+        .forecasts
+        .iter()
+        .map(|forecast| &forecast.summary.report);
+    let point = |celsius: f64| units.temperature(celsius) as u16;
+    let band = |range: Option<(f64, f64)>| range.map(|(lower, upper)| (point(lower), point(upper)));
+
     let _ = emitter.emit(
         id,
-        GraphComponentMessage {
-            max_temp_points,
-            min_temp_points,
+        GraphComponentMessage::Forecast {
+            max_temp_points: reports
+                .clone()
+                .map(|report| point(report.max_temp_c))
+                .collect(),
+            min_temp_points: reports
+                .clone()
+                .map(|report| point(report.min_temp_c))
+                .collect(),
+            max_temp_bands: reports
+                .clone()
+                .map(|report| band(report.max_temp_range_c()))
+                .collect(),
+            min_temp_bands: reports
+                .map(|report| band(report.min_temp_range_c()))
+                .collect(),
         },
     );
 }
+
+/// The max temperatures of each location that could be fetched, coloured by its position.
+pub(crate) fn max_temperature_series(locations: &[LocationForecast], units: Units) -> Vec<Series> {
+    locations
+        .iter()
+        .enumerate()
+        .filter_map(|(index, location)| {
+            let forecast = location.forecast.as_ref().ok()?;
+            let (colour, _) = SERIES_COLOURS[index % SERIES_COLOURS.len()];
+            Some(Series {
+                name: Some(forecast.location.name.clone()),
+                points: forecast
+                    .forecasts
+                    .iter()
+                    .map(|forecast| units.temperature(forecast.summary.report.max_temp_c) as u16)
+                    .collect(),
+                bands: vec![],
+                colour,
+            })
+        })
+        .collect()
+}
+
+/// Plots each series in its own colour with a legend, e.g. the max temperatures of several
+/// locations.
+pub(crate) fn update_overlay(
+    emitter: &Emitter,
+    id: ComponentId<GraphComponentMessage>,
+    series: Vec<Series>,
+) {
+    let _ = emitter.emit(id, GraphComponentMessage::Overlay(series));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+
+    fn fetched(name: &str) -> LocationForecast {
+        let mut forecast = test_forecast();
+        forecast.location.name = name.to_string();
+        LocationForecast {
+            location: name.to_string(),
+            forecast: Ok(forecast),
+        }
+    }
+
+    #[test]
+    fn plots_the_max_temperature_of_each_location() {
+        let locations = [
+            fetched("Dyserth"),
+            LocationForecast {
+                location: "Nowhere".to_string(),
+                forecast: Err("No results found".to_string()),
+            },
+            fetched("Rhyl"),
+        ];
+
+        let series = max_temperature_series(&locations, Units::Metric);
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].name.as_deref(), Some("Dyserth"));
+        assert_eq!(series[0].points.len(), 14);
+        assert_eq!(series[0].points[0], 18);
+        assert!(series[0].bands.is_empty());
+        // Coloured by position, so each matches its card even with one missing
+        assert!(matches!(series[0].colour, Color::Red));
+        assert_eq!(series[1].name.as_deref(), Some("Rhyl"));
+        assert!(matches!(series[1].colour, Color::Yellow));
+
+        let imperial = max_temperature_series(&locations[..1], Units::Imperial);
+        assert_eq!(imperial[0].points[0], 64);
+    }
+
+    #[test]
+    fn reuses_colours_beyond_the_palette() {
+        let locations: Vec<_> = (0..SERIES_COLOURS.len() + 1)
+            .map(|index| fetched(&format!("Place {}", index)))
+            .collect();
+
+        let series = max_temperature_series(&locations, Units::Metric);
+        assert!(matches!(series[SERIES_COLOURS.len()].colour, Color::Red));
+    }
+}
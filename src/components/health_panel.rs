@@ -0,0 +1,91 @@
+use anathema::component::{Component, ComponentId, Elements, Emitter, List, State, Value};
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+
+use crate::client::health::HealthIndex;
+use crate::client::WeatherSummaryReport;
+
+struct HealthPanel;
+
+impl HealthPanel {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl Component for HealthPanel {
+    type State = HealthPanelState;
+    type Message = HealthPanelMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        while state.indices.len() > 0 {
+            state.indices.pop_back();
+        }
+        for index in message.indices {
+            state.indices.push_back(index);
+        }
+    }
+}
+
+/// A row of the panel, coloured by the index's band.
+#[derive(State)]
+struct Index {
+    name: Value<String>,
+    description: Value<String>,
+    colour: Value<String>,
+}
+
+impl Index {
+    fn new(index: &HealthIndex) -> Self {
+        Self {
+            name: Value::new(format!("{:<10}", index.name)),
+            description: Value::new(index.describe()),
+            colour: Value::new(index.band.map_or("white", |band| band.colour()).to_string()),
+        }
+    }
+}
+
+#[derive(State)]
+struct HealthPanelState {
+    indices: Value<List<Index>>,
+}
+
+impl HealthPanelState {
+    fn new() -> Self {
+        Self {
+            indices: List::from_iter(vec![]),
+        }
+    }
+}
+
+pub struct HealthPanelMessage {
+    indices: Vec<Index>,
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<HealthPanelMessage> {
+    runtime
+        .register_component(
+            "healthPanel",
+            "src/templates/health_panel.aml",
+            HealthPanel::new(),
+            HealthPanelState::new(),
+        )
+        .unwrap()
+}
+
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<HealthPanelMessage>,
+    report: &WeatherSummaryReport,
+) {
+    let indices = report.health_indices().iter().map(Index::new).collect();
+    let _ = emitter.emit(id, HealthPanelMessage { indices });
+}
@@ -0,0 +1,152 @@
+use anathema::component::{Component, ComponentId, Elements, Emitter, List, State, Value};
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+use chrono::Utc;
+
+use crate::client::visibility::{DayVisibility, Visibility};
+use crate::client::{Units, WeatherDetailedReport, WeatherForecastResponse};
+
+/// How many hours the table shows from the current one.
+const HOURS: usize = 8;
+
+/// Each hour's column is this wide, enough for `V Poor`.
+const COLUMN_WIDTH: usize = 7;
+
+struct HourlyTable;
+
+impl HourlyTable {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl Component for HourlyTable {
+    type State = HourlyTableState;
+    type Message = HourlyTableMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        while state.hours.len() > 0 {
+            state.hours.pop_back();
+        }
+        for hour in message.hours {
+            state.hours.push_back(hour);
+        }
+        state.badge.set(message.badge);
+        state.badge_colour.set(message.badge_colour);
+    }
+}
+
+/// A column of the table, highlighted when visibility is poor.
+#[derive(State)]
+struct Hour {
+    time: Value<String>,
+    temperature: Value<String>,
+    rain: Value<String>,
+    visibility: Value<String>,
+    colour: Value<String>,
+}
+
+impl Hour {
+    fn new(report: &WeatherDetailedReport, units: Units) -> Self {
+        let column = |text: String| format!("{:>width$}", text, width = COLUMN_WIDTH);
+        Self {
+            time: Value::new(column(report.timeslot.clone())),
+            temperature: Value::new(column(format!(
+                "{:.0}°{}",
+                units.temperature(report.temperature_c),
+                units.temperature_symbol()
+            ))),
+            rain: Value::new(column(format!(
+                "{:.0}%",
+                report.precipitation_probability_in_percent
+            ))),
+            visibility: Value::new(column(
+                report
+                    .visibility
+                    .map_or("--", Visibility::abbreviation)
+                    .to_string(),
+            )),
+            colour: Value::new(
+                report
+                    .visibility
+                    .filter(|visibility| visibility.is_poor())
+                    .map_or("white", Visibility::colour)
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+#[derive(State)]
+struct HourlyTableState {
+    hours: Value<List<Hour>>,
+    badge: Value<String>,
+    badge_colour: Value<String>,
+}
+
+impl HourlyTableState {
+    fn new() -> Self {
+        Self {
+            hours: List::from_iter(vec![]),
+            badge: Value::new("".into()),
+            badge_colour: Value::new("white".into()),
+        }
+    }
+}
+
+pub struct HourlyTableMessage {
+    hours: Vec<Hour>,
+    badge: String,
+    badge_colour: String,
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<HourlyTableMessage> {
+    runtime
+        .register_component(
+            "hourlyTable",
+            "src/templates/hourly_table.aml",
+            HourlyTable::new(),
+            HourlyTableState::new(),
+        )
+        .unwrap()
+}
+
+/// Shows the next few hours and a badge with the worst of the day's visibility.
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<HourlyTableMessage>,
+    forecast: &WeatherForecastResponse,
+    units: Units,
+) {
+    let hours = forecast
+        .hourly_reports()
+        .skip(forecast.current_hour(Utc::now()))
+        .take(HOURS)
+        .map(|report| Hour::new(report, units))
+        .collect();
+    let today = forecast
+        .forecasts
+        .first()
+        .and_then(|day| DayVisibility::new(&day.detailed.reports));
+    let _ = emitter.emit(
+        id,
+        HourlyTableMessage {
+            hours,
+            badge: today.as_ref().map_or(
+                "Visibility not forecast".to_string(),
+                DayVisibility::describe,
+            ),
+            badge_colour: today
+                .map_or("white", |today| today.worst.colour())
+                .to_string(),
+        },
+    );
+}
@@ -2,15 +2,18 @@ use anathema::component::{Component, ComponentId, Elements, KeyCode, KeyEvent, V
 use anathema::prelude::*;
 use anathema::state::State;
 use anathema::widgets::components::events::KeyState;
-use std::sync::mpsc::Sender;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::client::{Units, WeathemaCommand};
 
 struct LocationInputComponent {
-    tx_input: Sender<String>,
+    tx_input: UnboundedSender<WeathemaCommand>,
+    units: Units,
 }
 
 impl LocationInputComponent {
-    fn new(tx_input: Sender<String>) -> Self {
-        Self { tx_input }
+    fn new(tx_input: UnboundedSender<WeathemaCommand>, units: Units) -> Self {
+        Self { tx_input, units }
     }
 }
 
@@ -65,7 +68,64 @@ impl Component for LocationInputComponent {
                 ..
             } => {
                 let location = state.location.to_ref().clone();
-                let _ = self.tx_input.send(location);
+                let _ = self.tx_input.send(WeathemaCommand::Fetch(location));
+            }
+            KeyEvent {
+                code: KeyCode::Char('r'),
+                ctrl: true,
+                state: KeyState::Press,
+            } => {
+                let _ = self.tx_input.send(WeathemaCommand::Refresh);
+            }
+            KeyEvent {
+                code: KeyCode::Char('u'),
+                ctrl: true,
+                state: KeyState::Press,
+            } => {
+                self.units = self.units.toggle();
+                let _ = self.tx_input.send(WeathemaCommand::ChangeUnits(self.units));
+            }
+            KeyEvent {
+                code: KeyCode::Char('e'),
+                ctrl: true,
+                state: KeyState::Press,
+            } => {
+                let _ = self.tx_input.send(WeathemaCommand::Export);
+            }
+            KeyEvent {
+                code: KeyCode::Char('d'),
+                ctrl: true,
+                state: KeyState::Press,
+            } => {
+                let _ = self.tx_input.send(WeathemaCommand::DismissAlerts);
+            }
+            KeyEvent {
+                code: KeyCode::Char('l'),
+                ctrl: true,
+                state: KeyState::Press,
+            } => {
+                let _ = self.tx_input.send(WeathemaCommand::ToggleDashboard);
+            }
+            KeyEvent {
+                code: KeyCode::Right,
+                state: KeyState::Press,
+                ..
+            } => {
+                let _ = self.tx_input.send(WeathemaCommand::NextDay);
+            }
+            KeyEvent {
+                code: KeyCode::Left,
+                state: KeyState::Press,
+                ..
+            } => {
+                let _ = self.tx_input.send(WeathemaCommand::PreviousDay);
+            }
+            KeyEvent {
+                code: KeyCode::Esc,
+                state: KeyState::Press,
+                ..
+            } => {
+                let _ = self.tx_input.send(WeathemaCommand::Cancel);
             }
             KeyEvent {
                 code: KeyCode::Char(c),
@@ -88,15 +148,16 @@ impl Component for LocationInputComponent {
 
 pub fn create_component(
     runtime: &mut anathema::runtime::RuntimeBuilder<TuiBackend, impl GlobalEvents>,
-    tx_input: Sender<String>,
+    tx_input: UnboundedSender<WeathemaCommand>,
     location: &Option<String>,
+    units: Units,
 ) -> ComponentId<()> {
     let location = location.clone();
     runtime
         .register_component(
             "locationInput",
             "src/templates/location_input.aml",
-            LocationInputComponent::new(tx_input),
+            LocationInputComponent::new(tx_input, units),
             LocationInputState::new(location.unwrap_or_else(|| "".into())),
         )
         .unwrap()
@@ -6,3 +6,14 @@ pub(crate) mod weather_display;
 pub(crate) mod spinner;
 pub(crate) mod main_holding;
 pub(crate) mod graph_component;
+pub(crate) mod status_line;
+pub(crate) mod alert_banner;
+pub(crate) mod dashboard;
+pub(crate) mod sun_arc;
+pub(crate) mod moon_phase;
+pub(crate) mod health_panel;
+pub(crate) mod current_conditions;
+pub(crate) mod barometer;
+pub(crate) mod hourly_table;
+pub(crate) mod sparkline_strip;
+pub(crate) mod spark;
@@ -0,0 +1,128 @@
+use anathema::component::{Component, ComponentId, Elements, Emitter, State, Value};
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+use chrono::{DateTime, Utc};
+use std::fs::read_to_string;
+
+use crate::astro::{self, MoonPhase, Observer};
+use crate::client::{sun, WeatherForecastResponse};
+
+struct MoonPhaseComponent;
+
+impl MoonPhaseComponent {
+    fn new() -> Self {
+        Self
+    }
+}
+
+fn image_path(phase: MoonPhase) -> &'static str {
+    match phase {
+        MoonPhase::New => "src/images/moon/new.txt",
+        MoonPhase::WaxingCrescent => "src/images/moon/waxing-crescent.txt",
+        MoonPhase::FirstQuarter => "src/images/moon/first-quarter.txt",
+        MoonPhase::WaxingGibbous => "src/images/moon/waxing-gibbous.txt",
+        MoonPhase::Full => "src/images/moon/full.txt",
+        MoonPhase::WaningGibbous => "src/images/moon/waning-gibbous.txt",
+        MoonPhase::LastQuarter => "src/images/moon/last-quarter.txt",
+        MoonPhase::WaningCrescent => "src/images/moon/waning-crescent.txt",
+    }
+}
+
+#[derive(State)]
+struct MoonPhaseState {
+    moon_image: Value<String>,
+    phase: Value<String>,
+    illumination: Value<String>,
+    rise_set: Value<String>,
+    next_full: Value<String>,
+    next_new: Value<String>,
+}
+
+impl MoonPhaseState {
+    fn new() -> Self {
+        Self {
+            moon_image: Value::new("".into()),
+            phase: Value::new("".into()),
+            illumination: Value::new("".into()),
+            rise_set: Value::new("".into()),
+            next_full: Value::new("".into()),
+            next_new: Value::new("".into()),
+        }
+    }
+}
+
+pub(crate) struct MoonPhaseMessage {
+    phase: MoonPhase,
+    illumination: f64,
+    rise_set: String,
+    next_full: String,
+    next_new: String,
+}
+
+impl Component for MoonPhaseComponent {
+    type State = MoonPhaseState;
+    type Message = MoonPhaseMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        state
+            .moon_image
+            .set(read_to_string(image_path(message.phase)).unwrap());
+        state.phase.set(message.phase.name().to_string());
+        state
+            .illumination
+            .set(format!("{:.0}% lit", message.illumination * 100.0));
+        state.rise_set.set(message.rise_set);
+        state.next_full.set(message.next_full);
+        state.next_new.set(message.next_new);
+    }
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<MoonPhaseMessage> {
+    runtime
+        .register_component(
+            "moonPhase",
+            "src/templates/moon_phase.aml",
+            MoonPhaseComponent::new(),
+            MoonPhaseState::new(),
+        )
+        .unwrap()
+}
+
+/// Shows tonight's moon at the forecast's location, worked out locally rather than from the forecast.
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<MoonPhaseMessage>,
+    forecast: &WeatherForecastResponse,
+) {
+    let now = Utc::now();
+    let offset = sun::offset(forecast);
+    let local = |at: Option<DateTime<Utc>>| match at {
+        Some(at) => at.with_timezone(&offset).format("%H:%M").to_string(),
+        None => "--:--".to_string(),
+    };
+    let day = |at: DateTime<Utc>| at.with_timezone(&offset).format("%a %-d %b").to_string();
+
+    let observer = Observer::new(forecast.location.latitude, forecast.location.longitude);
+    let today = now.with_timezone(&offset).date_naive();
+    let times = astro::moon_times(observer, today, offset);
+    let moon = astro::moon(now);
+
+    let _ = emitter.emit(
+        id,
+        MoonPhaseMessage {
+            phase: moon.phase,
+            illumination: moon.illumination,
+            rise_set: format!("↑ {}  ↓ {}", local(times.rise), local(times.set)),
+            next_full: format!("Full {}", day(astro::next_full_moon(now))),
+            next_new: format!("New  {}", day(astro::next_new_moon(now))),
+        },
+    );
+}
@@ -0,0 +1,56 @@
+/// Eighths of a character cell, from the lowest to the highest.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draws values as a line of block characters, scaled between the lowest and highest of them.
+///
+/// A series that doesn't change sits along the bottom.
+pub(crate) fn sparkline(values: &[f64]) -> String {
+    let lowest = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let highest = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    sparkline_between(values, lowest, highest)
+}
+
+/// Draws values as block characters on a fixed scale, such as 0 to 100 for a percentage, so a
+/// small change doesn't fill the line. Values outside the scale are drawn at its ends.
+pub(crate) fn sparkline_between(values: &[f64], lowest: f64, highest: f64) -> String {
+    let span = highest - lowest;
+    values
+        .iter()
+        .map(|value| {
+            let level = if span > 0.0 {
+                ((value - lowest) / span).clamp(0.0, 1.0) * (BLOCKS.len() - 1) as f64
+            } else {
+                0.0
+            };
+            BLOCKS[level.round() as usize]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_between_the_extremes() {
+        assert_eq!(
+            sparkline(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]),
+            "▁▂▃▄▅▆▇█"
+        );
+        assert_eq!(sparkline(&[1017.0, 1022.0, 1012.0]), "▅█▁");
+    }
+
+    #[test]
+    fn scales_to_a_fixed_range() {
+        assert_eq!(sparkline_between(&[0.0, 50.0, 100.0], 0.0, 100.0), "▁▅█");
+        // A few percent barely lifts off the bottom
+        assert_eq!(sparkline_between(&[0.0, 5.0, 10.0], 0.0, 100.0), "▁▁▂");
+        assert_eq!(sparkline_between(&[-10.0, 120.0], 0.0, 100.0), "▁█");
+    }
+
+    #[test]
+    fn draws_flat_and_empty_series() {
+        assert_eq!(sparkline(&[1019.0, 1019.0, 1019.0]), "▁▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
@@ -0,0 +1,144 @@
+use anathema::component::{Component, ComponentId, Elements, Emitter, State, Value};
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+use chrono::Utc;
+
+use crate::client::{Units, WeatherDetailedReport, WeatherForecastResponse};
+use crate::components::spark::{sparkline, sparkline_between};
+
+/// How many hourly reports the strip covers from the current hour.
+const HOURS: usize = 24;
+
+struct SparklineStrip;
+
+impl SparklineStrip {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl Component for SparklineStrip {
+    type State = SparklineStripState;
+    type Message = SparklineStripMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        match message {
+            SparklineStripMessage::Forecast {
+                temperature,
+                rain,
+                wind,
+            } => {
+                state.visible.set(true);
+                state.temperature.set(temperature);
+                state.rain.set(rain);
+                state.wind.set(wind);
+            }
+            SparklineStripMessage::Hidden => state.visible.set(false),
+        }
+    }
+}
+
+#[derive(State)]
+struct SparklineStripState {
+    visible: Value<bool>,
+    temperature: Value<String>,
+    rain: Value<String>,
+    wind: Value<String>,
+}
+
+impl SparklineStripState {
+    fn new() -> Self {
+        Self {
+            visible: Value::new(false),
+            temperature: Value::new("".into()),
+            rain: Value::new("".into()),
+            wind: Value::new("".into()),
+        }
+    }
+}
+
+pub(crate) enum SparklineStripMessage {
+    Forecast {
+        temperature: String,
+        rain: String,
+        wind: String,
+    },
+    /// While there is no forecast to sum up
+    Hidden,
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<SparklineStripMessage> {
+    runtime
+        .register_component(
+            "sparklineStrip",
+            "src/templates/sparkline_strip.aml",
+            SparklineStrip::new(),
+            SparklineStripState::new(),
+        )
+        .unwrap()
+}
+
+/// A sparkline followed by its lowest and highest values, such as `▁▃▅█ 12-18°C`.
+fn summarise(values: &[f64], line: String, unit: &str) -> String {
+    if values.is_empty() {
+        return "--".to_string();
+    }
+    let lowest = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let highest = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    format!("{} {:.0}-{:.0}{}", line, lowest, highest, unit)
+}
+
+/// Sums up the next day's temperature, chance of rain and wind from the hourly reports.
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<SparklineStripMessage>,
+    forecast: &WeatherForecastResponse,
+    units: Units,
+) {
+    let reports: Vec<&WeatherDetailedReport> = forecast
+        .hourly_reports()
+        .skip(forecast.current_hour(Utc::now()))
+        .take(HOURS)
+        .collect();
+    let temperature: Vec<f64> = reports
+        .iter()
+        .map(|report| units.temperature(report.temperature_c))
+        .collect();
+    let rain: Vec<f64> = reports
+        .iter()
+        .map(|report| report.precipitation_probability_in_percent)
+        .collect();
+    let wind: Vec<f64> = reports
+        .iter()
+        .map(|report| units.speed(report.wind_speed_kph))
+        .collect();
+
+    let _ = emitter.emit(
+        id,
+        SparklineStripMessage::Forecast {
+            temperature: summarise(
+                &temperature,
+                sparkline(&temperature),
+                &format!("°{}", units.temperature_symbol()),
+            ),
+            rain: summarise(&rain, sparkline_between(&rain, 0.0, 100.0), "%"),
+            wind: summarise(
+                &wind,
+                sparkline(&wind),
+                &format!(" {}", units.speed_symbol()),
+            ),
+        },
+    );
+}
+
+pub(crate) fn hide_component(emitter: &Emitter, id: ComponentId<SparklineStripMessage>) {
+    let _ = emitter.emit(id, SparklineStripMessage::Hidden);
+}
@@ -0,0 +1,64 @@
+use anathema::component::{Component, ComponentId, Elements, Emitter, State, Value};
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+
+struct StatusLine;
+
+impl StatusLine {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl Component for StatusLine {
+    type State = StatusLineState;
+    type Message = StatusLineMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        state.value.set(message.value);
+    }
+}
+
+#[derive(State)]
+struct StatusLineState {
+    value: Value<String>,
+}
+
+impl StatusLineState {
+    fn new() -> Self {
+        Self {
+            value: Value::new("".to_string()),
+        }
+    }
+}
+
+pub struct StatusLineMessage {
+    value: String,
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<StatusLineMessage> {
+    runtime
+        .register_component(
+            "statusLine",
+            "src/templates/status_line.aml",
+            StatusLine::new(),
+            StatusLineState::new(),
+        )
+        .unwrap()
+}
+
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<StatusLineMessage>,
+    value: String,
+) {
+    let _ = emitter.emit(id, StatusLineMessage { value });
+}
@@ -0,0 +1,156 @@
+use anathema::backend::tui::Style;
+use anathema::component::{Color, Component, ComponentId, Elements, Emitter, State, Value};
+use anathema::default_widgets::Canvas;
+use anathema::geometry::LocalPos;
+use anathema::prelude::{Context, GlobalEvents, TuiBackend};
+use anathema::runtime::RuntimeBuilder;
+
+use crate::client::sun::{self, Daylight};
+use crate::client::WeatherForecastResponse;
+
+/// The canvas is 21 by 6, the arc rising 4 rows above the horizon on its bottom row.
+const WIDTH: u16 = 21;
+const HORIZON: u16 = 5;
+const ARC_STEPS: u16 = 40;
+
+struct SunArc;
+
+impl SunArc {
+    fn new() -> Self {
+        Self
+    }
+
+    /// The cell a fraction of the way along the arc, from sunrise on the left to sunset on the right.
+    fn position(progress: f64) -> LocalPos {
+        let angle = std::f64::consts::PI * (1.0 - progress);
+        let half_width = (WIDTH - 1) as f64 / 2.0;
+        let x = half_width + (angle.cos() * half_width).round();
+        let y = HORIZON as f64 - (angle.sin() * (HORIZON - 1) as f64).round();
+        LocalPos::new(x as u16, y as u16)
+    }
+
+    fn draw(canvas: &mut Canvas, progress: Option<f64>) {
+        let mut horizon = Style::new();
+        horizon.set_fg(Color::DarkGrey);
+        for x in 0..WIDTH {
+            canvas.put('─', horizon, LocalPos::new(x, HORIZON));
+        }
+
+        // The sun's path so far is lit, the rest of the day is dim
+        let mut lit = Style::new();
+        lit.set_fg(Color::Yellow);
+        for step in 0..=ARC_STEPS {
+            let along = step as f64 / ARC_STEPS as f64;
+            let style = match progress {
+                Some(progress) if along <= progress => lit,
+                _ => horizon,
+            };
+            canvas.put('·', style, Self::position(along));
+        }
+
+        if let Some(progress) = progress {
+            canvas.put('●', lit, Self::position(progress));
+        }
+    }
+}
+
+#[derive(State)]
+struct SunArcState {
+    date: Value<String>,
+    sunrise: Value<String>,
+    sunset: Value<String>,
+    daylight: Value<String>,
+    change: Value<String>,
+}
+
+impl SunArcState {
+    fn new() -> Self {
+        Self {
+            date: Value::new("".into()),
+            sunrise: Value::new("".into()),
+            sunset: Value::new("".into()),
+            daylight: Value::new("".into()),
+            change: Value::new("".into()),
+        }
+    }
+}
+
+pub(crate) struct SunArcMessage {
+    daylight: Option<Daylight>,
+    /// Where the sun is, while it is up on the day shown
+    progress: Option<f64>,
+    change: Option<String>,
+}
+
+impl Component for SunArc {
+    type State = SunArcState;
+    type Message = SunArcMessage;
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        mut elements: Elements<'_, '_>,
+        _context: Context<'_, Self::State>,
+    ) {
+        match message.daylight {
+            Some(daylight) => {
+                state
+                    .date
+                    .set(daylight.date.format("%a %-d %b").to_string());
+                state
+                    .sunrise
+                    .set(daylight.sunrise.format("%H:%M").to_string());
+                state
+                    .sunset
+                    .set(daylight.sunset.format("%H:%M").to_string());
+                state.daylight.set(sun::format_length(daylight.length()));
+            }
+            None => {
+                state.date.set("".to_string());
+                state.sunrise.set("--:--".to_string());
+                state.sunset.set("--:--".to_string());
+                state.daylight.set("unknown".to_string());
+            }
+        }
+        state.change.set(message.change.unwrap_or_default());
+
+        elements.by_tag("canvas").first(|el, _| {
+            let canvas = el.to::<Canvas>();
+            canvas.clear();
+            Self::draw(canvas, message.progress);
+        });
+    }
+}
+
+pub fn create_component(
+    runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>,
+) -> ComponentId<SunArcMessage> {
+    runtime
+        .register_component(
+            "sunArc",
+            "src/templates/sun_arc.aml",
+            SunArc::new(),
+            SunArcState::new(),
+        )
+        .unwrap()
+}
+
+/// Shows the sun's day for one of the forecast's days, with the sun placed at the location's time.
+pub(crate) fn update_component(
+    emitter: &Emitter,
+    id: ComponentId<SunArcMessage>,
+    forecast: &WeatherForecastResponse,
+    day: usize,
+) {
+    let daylight = Daylight::for_day(forecast, day);
+    let now = sun::local_now(forecast, chrono::Utc::now());
+    let _ = emitter.emit(
+        id,
+        SunArcMessage {
+            daylight,
+            progress: daylight.and_then(|daylight| daylight.progress(now)),
+            change: sun::change(forecast, day),
+        },
+    );
+}
@@ -14,6 +14,7 @@ impl TemperatureRange {
 struct TemperatureRangeState {
     min_temperature: Value<f64>,
     max_temperature: Value<f64>,
+    unit: Value<String>,
 }
 
 impl TemperatureRangeState {
@@ -21,6 +22,7 @@ impl TemperatureRangeState {
         Self {
             min_temperature: Value::new(0.0),
             max_temperature: Value::new(0.0),
+            unit: Value::new("C".into()),
         }
     }
 }
@@ -28,13 +30,15 @@ impl TemperatureRangeState {
 pub(crate) struct TemperatureRangeMessage {
     min_temperature: f64,
     max_temperature: f64,
+    unit: String,
 }
 
 impl TemperatureRangeMessage {
-    fn new(temperature_range: (f64, f64)) -> Self {
+    fn new(temperature_range: (f64, f64), unit: String) -> Self {
         Self {
             min_temperature: temperature_range.0,
             max_temperature: temperature_range.1,
+            unit,
         }
     }
 }
@@ -51,6 +55,7 @@ impl Component for TemperatureRange {
     ) {
         state.min_temperature.set(message.min_temperature);
         state.max_temperature.set(message.max_temperature);
+        state.unit.set(message.unit);
     }
 }
 
@@ -71,11 +76,12 @@ pub fn update_component(
     emitter: &Emitter,
     temp_range_component_id: ComponentId<TemperatureRangeMessage>,
     temperature_range: (f64, f64),
+    unit: String,
 ) {
     emitter
         .emit(
             temp_range_component_id,
-            TemperatureRangeMessage::new(temperature_range),
+            TemperatureRangeMessage::new(temperature_range, unit),
         )
         .unwrap();
 }
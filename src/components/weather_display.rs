@@ -13,18 +13,22 @@ impl WeatherDisplay {
 #[derive(State)]
 struct WeatherDisplayState {
     is_loading: Value<bool>,
+    /// Show the dashboard's cards in place of the current location's details
+    dashboard: Value<bool>,
 }
 
 impl WeatherDisplayState {
     fn new() -> Self {
         Self {
             is_loading: Value::new(true),
+            dashboard: Value::new(false),
         }
     }
 }
 
-pub(crate) struct WeatherDisplayMessage {
-    is_loading: bool,
+pub(crate) enum WeatherDisplayMessage {
+    Loading(bool),
+    Dashboard(bool),
 }
 
 impl Component for WeatherDisplay {
@@ -38,7 +42,10 @@ impl Component for WeatherDisplay {
         _elements: Elements<'_, '_>,
         _context: Context<'_, Self::State>,
     ) {
-        state.is_loading.set(message.is_loading);
+        match message {
+            WeatherDisplayMessage::Loading(is_loading) => state.is_loading.set(is_loading),
+            WeatherDisplayMessage::Dashboard(dashboard) => state.dashboard.set(dashboard),
+        }
     }
 }
 
@@ -60,5 +67,13 @@ pub(crate) fn update_component(
     id: ComponentId<WeatherDisplayMessage>,
     is_loading: bool,
 ) {
-    let _ = emitter.emit(id, WeatherDisplayMessage { is_loading });
+    let _ = emitter.emit(id, WeatherDisplayMessage::Loading(is_loading));
+}
+
+pub(crate) fn show_dashboard(
+    emitter: &Emitter,
+    id: ComponentId<WeatherDisplayMessage>,
+    dashboard: bool,
+) {
+    let _ = emitter.emit(id, WeatherDisplayMessage::Dashboard(dashboard));
 }
@@ -1,3 +1,4 @@
+use crate::client::WeatherType;
 use crate::WeatherImageState;
 use anathema::component::{Component, ComponentId, Elements, Emitter};
 use anathema::prelude::{Context, GlobalEvents, TuiBackend};
@@ -34,34 +35,12 @@ impl Component for WeatherImage {
     ) {
         // TODO: Consider preloading the images and storing them in the state
         let WeatherImageMessage { weather_type } = message;
-        {
-            state.weather_image.set(match weather_type {
-                WeatherType::Sunny => read_to_string("src/images/sunny.txt").unwrap(),
-                WeatherType::PartlyCloudy => {
-                    read_to_string("src/images/partly-cloudy.txt").unwrap()
-                }
-                WeatherType::Cloudy => read_to_string("src/images/cloudy.txt").unwrap(),
-                WeatherType::Rainy => read_to_string("src/images/rainy.txt").unwrap(),
-                WeatherType::Snowy => read_to_string("src/images/snowy.txt").unwrap(),
-                WeatherType::Stormy => read_to_string("src/images/stormy.txt").unwrap(),
-                _ => read_to_string("src/images/unknown.txt").unwrap(),
-            });
-        }
+        state
+            .weather_image
+            .set(read_to_string(weather_type.image_path()).unwrap());
     }
 }
 
-#[allow(dead_code)]
-#[derive(Clone, Copy, Debug)]
-enum WeatherType {
-    Unknown = -1,
-    Sunny = 1,
-    PartlyCloudy = 3,
-    Cloudy,
-    Rainy,
-    Snowy,
-    Stormy,
-}
-
 pub fn create_component(
     runtime: &mut anathema::runtime::RuntimeBuilder<TuiBackend, impl GlobalEvents>,
 ) -> ComponentId<WeatherImageMessage> {
@@ -80,14 +59,7 @@ pub fn update_component(
     weather_image_component_id: ComponentId<WeatherImageMessage>,
     weather_type: u8,
 ) {
-    let weather_update = match weather_type {
-        0..=1 => WeatherType::Sunny,
-        2..=4 => WeatherType::PartlyCloudy,
-        5..=8 => WeatherType::Cloudy,
-        9..=12 => WeatherType::Rainy,
-        20 => WeatherType::Snowy, // TODO
-        _ => WeatherType::Unknown,
-    };
+    let weather_update = WeatherType::from_code(weather_type);
     emitter
         .emit(
             weather_image_component_id,
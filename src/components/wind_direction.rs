@@ -1,44 +1,104 @@
-use anathema::component::{Component, ComponentId, Elements, Emitter, Value};
+use anathema::backend::tui::Style;
+use anathema::component::{Color, Component, ComponentId, Elements, Emitter, Value};
+use anathema::default_widgets::Canvas;
+use anathema::geometry::LocalPos;
 use anathema::prelude::*;
 use anathema::state::State;
 
+use crate::client::{wind, Units, WindDirection};
+
+/// Rows from the centre of the compass to its rim, columns are doubled to keep it round.
+const RADIUS: f64 = 3.0;
+const CENTRE: (u16, u16) = (6, 3);
+const CARDINALS: [(WindDirection, char); 4] = [
+    (WindDirection::North, 'N'),
+    (WindDirection::East, 'E'),
+    (WindDirection::South, 'S'),
+    (WindDirection::West, 'W'),
+];
+
 #[derive(Default)]
 struct WindDirectionComponent;
 
-#[allow(dead_code)]
-enum WindDirection {
-    North,
-    NorthEast,
-    East,
-    SouthEast,
-    South,
-    SouthWest,
-    West,
-    NorthWest,
+impl WindDirectionComponent {
+    /// The cell `distance` rows out from the centre along a bearing.
+    fn position(degrees: f64, distance: f64) -> LocalPos {
+        let radians = degrees.to_radians();
+        let x = CENTRE.0 as f64 + (radians.sin() * distance * 2.0).round();
+        let y = CENTRE.1 as f64 - (radians.cos() * distance).round();
+        LocalPos::new(x as u16, y as u16)
+    }
+
+    fn draw_rose(canvas: &mut Canvas) {
+        let mut style = Style::new();
+        style.set_fg(Color::DarkGrey);
+        for point in wind::POINTS {
+            let c = CARDINALS
+                .iter()
+                .find(|(cardinal, _)| *cardinal == point)
+                .map_or('·', |(_, c)| *c);
+            canvas.put(c, style, Self::position(point.degrees(), RADIUS));
+        }
+    }
+
+    /// Draws an arrow through the centre pointing the way the wind blows.
+    fn draw_arrow(canvas: &mut Canvas, direction: WindDirection, colour: Color) {
+        let mut style = Style::new();
+        style.set_fg(colour);
+
+        let degrees = direction.downwind_degrees();
+        let shaft = match (degrees / 45.0).round() as usize % 4 {
+            0 => '│',
+            1 => '╱',
+            2 => '─',
+            _ => '╲',
+        };
+        let length = RADIUS - 1.0;
+        let mut distance = -length;
+        while distance < length {
+            canvas.put(shaft, style, Self::position(degrees, distance));
+            distance += 0.5;
+        }
+        canvas.put(direction.arrow(), style, Self::position(degrees, length));
+    }
+}
+
+/// Calm to breezy is green, getting blustery is yellow, gales are red and storms magenta.
+fn strength_colour(force: u8) -> (Color, &'static str) {
+    match force {
+        0..=3 => (Color::Green, "green"),
+        4..=6 => (Color::Yellow, "yellow"),
+        7..=9 => (Color::Red, "red"),
+        _ => (Color::Magenta, "magenta"),
+    }
 }
 
 #[derive(State)]
 struct WindDirectionState {
-    direction: Value<String>,
+    speed: Value<String>,
+    gust: Value<String>,
+    beaufort: Value<String>,
+    colour: Value<String>,
 }
 
 impl WindDirectionState {
     fn new() -> Self {
         Self {
-            direction: Value::new("".into()),
+            speed: Value::new("".into()),
+            gust: Value::new("".into()),
+            beaufort: Value::new("".into()),
+            colour: Value::new("white".into()),
         }
     }
 }
 
 pub(crate) struct WindDirectionMessage {
     direction: String,
+    speed_kph: f64,
+    gust_kph: Option<f64>,
+    units: Units,
 }
 
-impl WindDirectionMessage {
-    fn new(direction: String) -> Self {
-        Self { direction }
-    }
-}
 impl Component for WindDirectionComponent {
     type State = WindDirectionState;
     type Message = WindDirectionMessage;
@@ -47,21 +107,36 @@ impl Component for WindDirectionComponent {
         &mut self,
         message: Self::Message,
         state: &mut Self::State,
-        _elements: Elements<'_, '_>,
+        mut elements: Elements<'_, '_>,
         _context: Context<'_, Self::State>,
     ) {
-        // let dir = match message.direction.as_str() {
-        //     "N" => WindDirection::North,
-        //     "NE" => WindDirection::NorthEast,
-        //     "E" => WindDirection::East,
-        //     "SE" => WindDirection::SouthEast,
-        //     "S" => WindDirection::South,
-        //     "SW" => WindDirection::SouthWest,
-        //     "W" => WindDirection::West,
-        //     "NW" => WindDirection::NorthWest,
-        //     _ => WindDirection::North,
-        // };
-        state.direction.set(message.direction);
+        let units = message.units;
+        let direction = WindDirection::parse(&message.direction);
+        let (force, description) = wind::beaufort(message.speed_kph);
+        let (colour, colour_name) = strength_colour(force);
+
+        state.speed.set(format!(
+            "{:.0} {} {}",
+            units.speed(message.speed_kph),
+            units.speed_symbol(),
+            direction.map_or(message.direction.as_str(), |direction| direction
+                .abbreviation())
+        ));
+        state.gust.set(match message.gust_kph {
+            Some(gust) => format!("Gusts {:.0} {}", units.speed(gust), units.speed_symbol()),
+            None => String::new(),
+        });
+        state.beaufort.set(format!("F{} {}", force, description));
+        state.colour.set(colour_name.to_string());
+
+        elements.by_tag("canvas").first(|el, _| {
+            let canvas = el.to::<Canvas>();
+            canvas.clear();
+            Self::draw_rose(canvas);
+            if let Some(direction) = direction {
+                Self::draw_arrow(canvas, direction, colour);
+            }
+        });
     }
 }
 
@@ -80,13 +155,21 @@ pub fn create_component(
 
 pub fn update_component(
     emitter: &Emitter,
-    temp_range_component_id: ComponentId<WindDirectionMessage>,
+    wind_direction_component_id: ComponentId<WindDirectionMessage>,
     wind_direction: String,
+    speed_kph: f64,
+    gust_kph: Option<f64>,
+    units: Units,
 ) {
     emitter
         .emit(
-            temp_range_component_id,
-            WindDirectionMessage::new(wind_direction),
+            wind_direction_component_id,
+            WindDirectionMessage {
+                direction: wind_direction,
+                speed_kph,
+                gust_kph,
+                units,
+            },
         )
         .unwrap();
 }
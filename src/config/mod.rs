@@ -0,0 +1,157 @@
+use crate::rules::Rule;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Settings read from `config.toml`, see the readme for an example.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Locations reported on by the background services, e.g. the metrics exporter
+    pub locations: Vec<String>,
+    /// The broker `weathema mqtt` publishes to
+    pub mqtt: MqttConfig,
+    /// Alert rules checked after each refresh in the TUI
+    pub rules: Vec<Rule>,
+}
+
+/// The `[mqtt]` section.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub client_id: String,
+    /// State topics are published under `<topic_prefix>/<location id>/`
+    pub topic_prefix: String,
+    /// Home Assistant's discovery prefix
+    pub discovery_prefix: String,
+    /// Seconds between refreshes
+    pub interval: u64,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 1883,
+            username: None,
+            password: None,
+            client_id: "weathema".to_string(),
+            topic_prefix: "weathema".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+            interval: 900,
+        }
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/weathema/config.toml`, falling back to `~/.config/weathema/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join("weathema").join("config.toml"))
+    }
+
+    /// Loads the given file, or the default one if it exists.
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Config::default()),
+            },
+        };
+
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Couldn't read {}: {}", path.display(), err))?;
+        Self::parse(&contents).map_err(|err| format!("Invalid {}: {}", path.display(), err).into())
+    }
+
+    fn parse(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_locations() {
+        let config = Config::parse(
+            r#"
+            locations = ["Dyserth", "53.30,-3.41"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.locations, ["Dyserth", "53.30,-3.41"]);
+    }
+
+    #[test]
+    fn parses_mqtt_section() {
+        let config = Config::parse(
+            r#"
+            [mqtt]
+            host = "broker.lan"
+            username = "weathema"
+            password = "secret"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.mqtt.host, "broker.lan");
+        assert_eq!(config.mqtt.port, 1883);
+        assert_eq!(config.mqtt.username.as_deref(), Some("weathema"));
+        assert_eq!(config.mqtt.discovery_prefix, "homeassistant");
+    }
+
+    #[test]
+    fn parses_rules() {
+        let config = Config::parse(
+            r#"
+            [[rules]]
+            name = "Rain tomorrow"
+            when = "rain > 70"
+            day = "tomorrow"
+            webhook = "http://localhost:9000/hook"
+
+            [[rules]]
+            name = "Frost"
+            when = "min_temp < 0"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.rules.len(), 2);
+        assert_eq!(config.rules[0].when.to_string(), "rain > 70");
+        assert!(config.rules[1].webhook.is_none());
+
+        assert!(Config::parse("[[rules]]\nname = \"x\"\nwhen = \"rain ~ 1\"").is_err());
+    }
+
+    #[test]
+    fn rejects_rules_that_cannot_be_checked_hourly() {
+        let rule = |settings: &str| {
+            Config::parse(&format!("[[rules]]\nname = \"x\"\n{}", settings))
+                .map_err(|err| err.to_string())
+        };
+
+        assert!(rule("when = \"rain > 50\"\nwithin = 2").is_ok());
+        assert!(rule("when = \"uv > 5\"").is_ok());
+        assert!(rule("when = \"uv > 5\"\nwithin = 2")
+            .unwrap_err()
+            .contains("no UV index"));
+        assert!(rule("when = \"rain > 50\"\nwithin = 2\nday = \"tomorrow\"")
+            .unwrap_err()
+            .contains("both `within` and `day`"));
+        assert!(rule("when = \"rain > 50\"\nsoon = true").is_err());
+    }
+
+    #[test]
+    fn defaults_missing_settings() {
+        assert!(Config::parse("").unwrap().locations.is_empty());
+        assert!(Config::parse("unknown = 1").is_err());
+    }
+}
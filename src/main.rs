@@ -1,19 +1,43 @@
+mod astro;
+mod backend;
+mod cli;
 mod client;
 mod components;
+mod config;
+mod meteo;
+mod mqtt;
+mod output;
+mod rules;
+mod server;
 
-use crate::client::{WeathemaComponentMessaging, WeatherAPI};
+use crate::backend::WeathemaComponentMessaging;
+use crate::client::{Units, WeathemaCommand, WeatherAPI};
 use anathema::component::State;
 use anathema::prelude::*;
 use anathema::runtime::RuntimeBuilder;
 use anathema::state::Value;
 use clap::Parser;
 use std::fs::read_to_string;
-use std::sync::mpsc;
-use std::sync::mpsc::{Sender, TryRecvError};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use tokio::sync::mpsc;
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<cli::Command>,
+    /// Place name, `lat,lon` coordinates, UK postcode or BBC location id
     location: Option<String>,
+    /// BBC location id, e.g. 2650584
+    #[arg(long, conflicts_with = "location")]
+    id: Option<usize>,
+    #[arg(long, global = true, value_enum, default_value_t = Units::Metric)]
+    units: Units,
+    /// Config file, defaults to ~/.config/weathema/config.toml
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
 }
 
 #[derive(State)]
@@ -30,9 +54,21 @@ impl WeatherImageState {
 }
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     let args = Args::parse();
-    let location = args.location;
+    if let Some(command) = args.command {
+        return cli::run(command, args.units, args.config.as_deref()).await;
+    }
+
+    let location = args.id.map(|id| id.to_string()).or(args.location);
+    let units = args.units;
+    let config = match config::Config::load(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
 
     let template = read_to_string("src/templates/index.aml").unwrap();
 
@@ -47,7 +83,8 @@ async fn main() {
 
     let mut runtime = Runtime::builder(doc, backend);
 
-    let (tx_input, rx_input) = mpsc::channel::<String>();
+    let (tx_input, rx_input) = mpsc::unbounded_channel::<WeathemaCommand>();
+    let tx_shutdown = tx_input.clone();
 
     register_static_component(&mut runtime);
     let spinner_id = components::spinner::create_component(&mut runtime);
@@ -57,14 +94,29 @@ async fn main() {
     let temperature_range_id = components::temperature_range::create_component(&mut runtime);
     let wind_direction_id = components::wind_direction::create_component(&mut runtime);
     let graph_component_id = components::graph_component::create_component(&mut runtime);
+    let status_line_id = components::status_line::create_component(&mut runtime);
+    let alert_banner_id = components::alert_banner::create_component(&mut runtime);
+    let dashboard_id = components::dashboard::create_component(&mut runtime);
+    let sun_arc_id = components::sun_arc::create_component(&mut runtime);
+    let moon_phase_id = components::moon_phase::create_component(&mut runtime);
+    let health_panel_id = components::health_panel::create_component(&mut runtime);
+    let current_conditions_id = components::current_conditions::create_component(&mut runtime);
+    let barometer_id = components::barometer::create_component(&mut runtime);
+    let hourly_table_id = components::hourly_table::create_component(&mut runtime);
+    let sparkline_strip_id = components::sparkline_strip::create_component(&mut runtime);
     let _location_input_id =
-        components::location_input::create_component(&mut runtime, tx_input, &location);
+        components::location_input::create_component(&mut runtime, tx_input, &location, units);
 
-    let (tx, rx) = mpsc::channel::<WeathemaComponentMessaging>();
+    let (tx, mut rx) = mpsc::unbounded_channel::<WeathemaComponentMessaging>();
 
-    tokio::spawn(async move {
-        poll_backend_service(tx, rx_input, &location).await;
-    });
+    let backend = tokio::spawn(backend::poll_backend_service(
+        WeatherAPI::new(),
+        tx,
+        rx_input,
+        location,
+        units,
+        config,
+    ));
 
     let emitter = runtime.emitter();
 
@@ -77,10 +129,16 @@ async fn main() {
         "Enter location".to_string(),
     );
 
-    tokio::spawn(async move {
-        while let Ok(weather_message) = rx.recv() {
+    let dispatcher = tokio::spawn(async move {
+        // The dashboard takes over the graph while it is open
+        let mut dashboard_open = false;
+        let mut has_forecast = false;
+
+        while let Some(weather_message) = rx.recv().await {
             match weather_message {
                 WeathemaComponentMessaging::Waiting => {
+                    has_forecast = false;
+                    components::sparkline_strip::hide_component(&emitter, sparkline_strip_id);
                     components::spinner::update_component(&emitter, spinner_id, true);
                     components::weather_display::update_component(
                         &emitter,
@@ -94,29 +152,73 @@ async fn main() {
                         "Loading...".to_string(),
                     );
                 }
-                WeathemaComponentMessaging::Received(weather_update) => {
+                WeathemaComponentMessaging::Received(weather_update, units, day) => {
                     components::temperature_range::update_component(
                         &emitter,
                         temperature_range_id,
                         (
-                            weather_update.forecasts[0].summary.report.min_temp_c,
-                            weather_update.forecasts[0].summary.report.max_temp_c,
+                            units
+                                .temperature(weather_update.forecasts[0].summary.report.min_temp_c),
+                            units
+                                .temperature(weather_update.forecasts[0].summary.report.max_temp_c),
                         ),
+                        units.temperature_symbol().to_string(),
                     );
                     components::weather_image::update_component(
                         &emitter,
                         weather_image_component_id,
                         weather_update.forecasts[0].summary.report.weather_type,
                     );
+                    let today = &weather_update.forecasts[0].summary.report;
                     components::wind_direction::update_component(
                         &emitter,
                         wind_direction_id,
-                        weather_update.forecasts[0]
-                            .summary
-                            .report
-                            .wind_direction
-                            .clone(),
+                        today.wind_direction.clone(),
+                        today.wind_speed_kph,
+                        today.gust_speed_kph,
+                        units,
+                    );
+                    components::sun_arc::update_component(
+                        &emitter,
+                        sun_arc_id,
+                        &weather_update,
+                        day,
+                    );
+                    components::moon_phase::update_component(
+                        &emitter,
+                        moon_phase_id,
+                        &weather_update,
                     );
+                    components::health_panel::update_component(
+                        &emitter,
+                        health_panel_id,
+                        &weather_update.forecasts[0].summary.report,
+                    );
+                    components::barometer::update_component(
+                        &emitter,
+                        barometer_id,
+                        &weather_update,
+                    );
+                    components::hourly_table::update_component(
+                        &emitter,
+                        hourly_table_id,
+                        &weather_update,
+                        units,
+                    );
+                    components::sparkline_strip::update_component(
+                        &emitter,
+                        sparkline_strip_id,
+                        &weather_update,
+                        units,
+                    );
+                    if let Some(report) = weather_update.current_report(chrono::Utc::now()) {
+                        components::current_conditions::update_component(
+                            &emitter,
+                            current_conditions_id,
+                            report,
+                            units,
+                        );
+                    }
                     components::weather_display::update_component(
                         &emitter,
                         weather_display_id,
@@ -129,22 +231,95 @@ async fn main() {
                         false,
                         "Loaded".to_string(),
                     );
-                    components::graph_component::update_component(
+                    has_forecast = true;
+                    if !dashboard_open {
+                        components::graph_component::update_component(
+                            &emitter,
+                            graph_component_id,
+                            &weather_update,
+                            units,
+                        );
+                    }
+                }
+                WeathemaComponentMessaging::Cancelled => {
+                    has_forecast = false;
+                    components::sparkline_strip::hide_component(&emitter, sparkline_strip_id);
+                    components::spinner::update_component(&emitter, spinner_id, false);
+                    components::weather_display::update_component(
+                        &emitter,
+                        weather_display_id,
+                        true,
+                    );
+                    components::main_holding::update_component(
+                        &emitter,
+                        main_holding_id,
+                        true,
+                        "Cancelled".to_string(),
+                    );
+                }
+                WeathemaComponentMessaging::Status(status) => {
+                    components::status_line::update_component(&emitter, status_line_id, status);
+                }
+                WeathemaComponentMessaging::Alerts(alerts, units) => {
+                    let (text, colour) = rules::severe::banner(&alerts, units);
+                    components::alert_banner::update_component(
+                        &emitter,
+                        alert_banner_id,
+                        text,
+                        colour.to_string(),
+                    );
+                }
+                WeathemaComponentMessaging::Dashboard(Some(locations), units) => {
+                    dashboard_open = true;
+                    components::graph_component::update_overlay(
                         &emitter,
                         graph_component_id,
-                        weather_update
-                            .forecasts
-                            .iter()
-                            .map(|forecast| forecast.summary.report.max_temp_c as u16)
-                            .collect(),
-                        weather_update
-                            .forecasts
-                            .iter()
-                            .map(|forecast| forecast.summary.report.min_temp_c as u16)
-                            .collect(),
+                        components::graph_component::max_temperature_series(&locations, units),
+                    );
+                    components::dashboard::update_component(
+                        &emitter,
+                        dashboard_id,
+                        locations,
+                        units,
+                    );
+                    components::weather_display::show_dashboard(&emitter, weather_display_id, true);
+                    components::weather_display::update_component(
+                        &emitter,
+                        weather_display_id,
+                        false,
+                    );
+                    components::main_holding::update_component(
+                        &emitter,
+                        main_holding_id,
+                        false,
+                        "".to_string(),
                     );
                 }
+                WeathemaComponentMessaging::Dashboard(None, _) => {
+                    dashboard_open = false;
+                    components::weather_display::show_dashboard(
+                        &emitter,
+                        weather_display_id,
+                        false,
+                    );
+                    // Otherwise the backend sends the forecast again
+                    if !has_forecast {
+                        components::weather_display::update_component(
+                            &emitter,
+                            weather_display_id,
+                            true,
+                        );
+                        components::main_holding::update_component(
+                            &emitter,
+                            main_holding_id,
+                            true,
+                            "Enter location".to_string(),
+                        );
+                    }
+                }
                 WeathemaComponentMessaging::Error(reason) => {
+                    has_forecast = false;
+                    components::sparkline_strip::hide_component(&emitter, sparkline_strip_id);
                     components::spinner::update_component(&emitter, spinner_id, false);
                     components::weather_display::update_component(
                         &emitter,
@@ -164,6 +339,16 @@ async fn main() {
 
     let mut runtime = runtime.finish().unwrap();
     runtime.run();
+
+    // The UI has gone, let the backend finish what it is doing and wind down
+    let _ = tx_shutdown.send(WeathemaCommand::Quit);
+    let _ = tokio::time::timeout(Duration::from_secs(2), async {
+        let _ = backend.await;
+        let _ = dispatcher.await;
+    })
+    .await;
+
+    ExitCode::SUCCESS
 }
 
 fn register_static_component(runtime: &mut RuntimeBuilder<TuiBackend, impl GlobalEvents>) {
@@ -179,62 +364,3 @@ fn register_static_component(runtime: &mut RuntimeBuilder<TuiBackend, impl Globa
         .register_component("footer", "src/templates/footer.aml", (), ())
         .unwrap();
 }
-
-async fn poll_backend_service(
-    tx: Sender<WeathemaComponentMessaging>,
-    rx: mpsc::Receiver<String>,
-    initial_location: &Option<String>,
-) {
-    let weather_api = WeatherAPI::new();
-
-    if let Some(location) = initial_location {
-        if !get_weather(&tx, &weather_api, location).await {
-            return;
-        }
-    }
-
-    loop {
-        match rx.try_recv() {
-            Ok(entered_location) => {
-                if !get_weather(&tx, &weather_api, &entered_location).await {
-                    return;
-                }
-            }
-            Err(err) => match err {
-                TryRecvError::Empty => {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                }
-                TryRecvError::Disconnected => {
-                    eprintln!("Disconnected Error: {}", err);
-                    return;
-                }
-            },
-        }
-    }
-}
-
-async fn get_weather(
-    tx: &Sender<WeathemaComponentMessaging>,
-    weather_api: &WeatherAPI,
-    entered_location: &str,
-) -> bool {
-    tx.send(WeathemaComponentMessaging::Waiting).unwrap();
-
-    match weather_api.get_weather(entered_location).await {
-        Ok(information) => {
-            // Send the weather update to the main thread
-            if tx
-                .send(WeathemaComponentMessaging::Received(information))
-                .is_err()
-            {
-                println!("Receiver dropped");
-                return false;
-            }
-        }
-        Err(err) => {
-            tx.send(WeathemaComponentMessaging::Error(err.to_string()))
-                .unwrap();
-        }
-    }
-    true
-}
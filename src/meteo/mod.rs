@@ -0,0 +1,170 @@
+//! Derived weather quantities, for providers that only report the basic measurements.
+//!
+//! Temperatures are in °C, relative humidity in percent and wind speeds in km/h throughout.
+
+/// Magnus formula coefficients, good to 0.1°C between -40°C and 50°C.
+const MAGNUS_A: f64 = 17.62;
+const MAGNUS_B: f64 = 243.12;
+
+/// Wind chill is only defined for cold air and some wind.
+const WIND_CHILL_MAX_C: f64 = 10.0;
+const WIND_CHILL_MIN_KPH: f64 = 4.8;
+
+/// The heat index is only meaningful when it is warm, 80°F.
+const HEAT_INDEX_MIN_C: f64 = 26.7;
+
+fn to_fahrenheit(celsius: f64) -> f64 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+fn to_celsius(fahrenheit: f64) -> f64 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+/// The temperature the air would have to cool to for dew to form.
+///
+/// `None` for perfectly dry air, which never reaches one.
+pub(crate) fn dew_point(temperature: f64, humidity: f64) -> Option<f64> {
+    if humidity <= 0.0 {
+        return None;
+    }
+    let gamma = (humidity / 100.0).ln() + MAGNUS_A * temperature / (MAGNUS_B + temperature);
+    Some(MAGNUS_B * gamma / (MAGNUS_A - gamma))
+}
+
+/// How hot it feels once humidity is taken into account, from the US National Weather Service.
+///
+/// Uses the Rothfusz regression with the NWS adjustments, falling back to Steadman's simpler
+/// formula when that gives less than 80°F as the regression doesn't hold there.
+pub(crate) fn heat_index(temperature: f64, humidity: f64) -> f64 {
+    let t = to_fahrenheit(temperature);
+    let rh = humidity;
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if (simple + t) / 2.0 < 80.0 {
+        return to_celsius(simple);
+    }
+
+    let mut index = -42.379 + 2.04901523 * t + 10.14333127 * rh
+        - 0.22475541 * t * rh
+        - 0.00683783 * t * t
+        - 0.05481717 * rh * rh
+        + 0.00122874 * t * t * rh
+        + 0.00085282 * t * rh * rh
+        - 0.00000199 * t * t * rh * rh;
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        index += (rh - 85.0) / 10.0 * (87.0 - t) / 5.0;
+    }
+    to_celsius(index)
+}
+
+/// How cold it feels in the wind, the index used in Canada, the US and the UK.
+pub(crate) fn wind_chill(temperature: f64, wind_speed: f64) -> f64 {
+    let wind = wind_speed.powf(0.16);
+    13.12 + 0.6215 * temperature - 11.37 * wind + 0.3965 * temperature * wind
+}
+
+/// Whether it is cold and windy enough for the wind chill to apply.
+pub(crate) fn wind_chill_applies(temperature: f64, wind_speed: f64) -> bool {
+    temperature <= WIND_CHILL_MAX_C && wind_speed > WIND_CHILL_MIN_KPH
+}
+
+/// Whether it is warm enough for the heat index to apply.
+pub(crate) fn heat_index_applies(temperature: f64) -> bool {
+    temperature >= HEAT_INDEX_MIN_C
+}
+
+/// Environment Canada's measure of how humid heat feels, from the temperature and dew point.
+pub(crate) fn humidex(temperature: f64, dew_point: f64) -> f64 {
+    let vapour_pressure = 6.11 * (5417.7530 * (1.0 / 273.16 - 1.0 / (273.15 + dew_point))).exp();
+    temperature + 0.5555 * (vapour_pressure - 10.0)
+}
+
+/// Steadman's apparent temperature for shade, as used by the Australian Bureau of Meteorology.
+pub(crate) fn apparent_temperature(temperature: f64, humidity: f64, wind_speed: f64) -> f64 {
+    let vapour_pressure =
+        humidity / 100.0 * 6.105 * (17.27 * temperature / (237.7 + temperature)).exp();
+    let wind = wind_speed / 3.6;
+    temperature + 0.33 * vapour_pressure - 0.70 * wind - 4.00
+}
+
+/// What the temperature feels like: wind chill in the cold, the heat index in humid warmth and
+/// the air temperature in between.
+pub(crate) fn feels_like(temperature: f64, humidity: Option<f64>, wind_speed: f64) -> f64 {
+    if wind_chill_applies(temperature, wind_speed) {
+        return wind_chill(temperature, wind_speed);
+    }
+    match humidity {
+        Some(humidity) if heat_index_applies(temperature) => heat_index(temperature, humidity),
+        _ => temperature,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn finds_the_dew_point() {
+        // Published dew point tables give 9.3°C, 20.0°C and -2.1°C
+        assert_near(dew_point(20.0, 50.0).unwrap(), 9.3, 0.1);
+        assert_near(dew_point(30.0, 55.0).unwrap(), 20.0, 0.1);
+        assert_near(dew_point(5.0, 60.0).unwrap(), -2.1, 0.2);
+        // Saturated air is at its dew point
+        assert_near(dew_point(12.0, 100.0).unwrap(), 12.0, 0.001);
+        assert_eq!(dew_point(12.0, 0.0), None);
+        assert_eq!(dew_point(12.0, -5.0), None);
+    }
+
+    #[test]
+    fn matches_the_heat_index_chart() {
+        // NWS heat index chart, converted from °F: 90°F at 70% is 106°F, 96°F at 55% is 112°F
+        assert_near(heat_index(to_celsius(90.0), 70.0), to_celsius(106.0), 0.6);
+        assert_near(heat_index(to_celsius(96.0), 55.0), to_celsius(112.0), 0.6);
+        // 80°F at 40% feels like 80°F
+        assert_near(heat_index(to_celsius(80.0), 40.0), to_celsius(80.0), 0.6);
+    }
+
+    #[test]
+    fn matches_the_wind_chill_chart() {
+        // Environment Canada wind chill chart
+        assert_eq!(wind_chill(-10.0, 20.0).round(), -18.0);
+        assert_eq!(wind_chill(0.0, 10.0).round(), -3.0);
+        assert_eq!(wind_chill(-25.0, 40.0).round(), -41.0);
+    }
+
+    #[test]
+    fn matches_the_humidex_chart() {
+        // Environment Canada humidex chart
+        assert_eq!(humidex(30.0, 15.0).round(), 34.0);
+        assert_eq!(humidex(35.0, 25.0).round(), 47.0);
+    }
+
+    #[test]
+    fn finds_the_apparent_temperature() {
+        // 25°C at 50% in a 2 m/s breeze, worked through the BoM formula
+        assert_near(apparent_temperature(25.0, 50.0, 7.2), 24.8, 0.05);
+        assert_near(apparent_temperature(10.0, 80.0, 0.0), 9.2, 0.05);
+    }
+
+    #[test]
+    fn picks_the_index_that_applies() {
+        assert_eq!(feels_like(5.0, Some(80.0), 20.0), wind_chill(5.0, 20.0));
+        // Calm cold air just feels cold
+        assert_eq!(feels_like(5.0, Some(80.0), 3.0), 5.0);
+        assert_eq!(feels_like(18.0, Some(61.0), 17.0), 18.0);
+        assert_eq!(feels_like(32.0, Some(70.0), 10.0), heat_index(32.0, 70.0));
+        assert_eq!(feels_like(32.0, None, 10.0), 32.0);
+    }
+}
@@ -0,0 +1,433 @@
+use crate::client::{WeatherAPI, WeatherForecastResponse};
+use crate::config::MqttConfig;
+use crate::output::json::{DailyDocument, DayOutput, HourOutput, LocationOutput};
+use chrono::{DateTime, Utc};
+use rumqttc::{AsyncClient, Event, Incoming, LastWill, MqttOptions, Outgoing, QoS};
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const MAX_PACKET_SIZE: usize = 256 * 1024;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// A retained message to publish.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Message {
+    pub topic: String,
+    pub payload: String,
+}
+
+/// The payload of `<prefix>/<location id>/state`.
+#[derive(Serialize)]
+struct StatePayload<'a> {
+    location: LocationOutput<'a>,
+    current: HourOutput<'a>,
+    today: DayOutput<'a>,
+}
+
+/// A Home Assistant sensor read from the state payload.
+struct Sensor {
+    key: &'static str,
+    name: &'static str,
+    value: &'static str,
+    unit: Option<&'static str>,
+    device_class: Option<&'static str>,
+}
+
+const SENSORS: [Sensor; 10] = [
+    Sensor {
+        key: "temperature",
+        name: "Temperature",
+        value: "current.temperature_c",
+        unit: Some("°C"),
+        device_class: Some("temperature"),
+    },
+    Sensor {
+        key: "temperature_max",
+        name: "Max temperature today",
+        value: "today.max_temp_c",
+        unit: Some("°C"),
+        device_class: Some("temperature"),
+    },
+    Sensor {
+        key: "temperature_min",
+        name: "Min temperature today",
+        value: "today.min_temp_c",
+        unit: Some("°C"),
+        device_class: Some("temperature"),
+    },
+    Sensor {
+        key: "wind_speed",
+        name: "Wind speed",
+        value: "current.wind_speed_kph",
+        unit: Some("km/h"),
+        device_class: Some("wind_speed"),
+    },
+    Sensor {
+        key: "wind_direction",
+        name: "Wind direction",
+        value: "current.wind_direction",
+        unit: None,
+        device_class: None,
+    },
+    Sensor {
+        key: "precipitation_probability",
+        name: "Chance of rain",
+        value: "current.precipitation_probability_in_percent",
+        unit: Some("%"),
+        device_class: None,
+    },
+    Sensor {
+        key: "humidity",
+        name: "Humidity",
+        value: "current.humidity",
+        unit: Some("%"),
+        device_class: Some("humidity"),
+    },
+    Sensor {
+        key: "pressure",
+        name: "Pressure",
+        value: "current.pressure",
+        unit: Some("hPa"),
+        device_class: Some("atmospheric_pressure"),
+    },
+    Sensor {
+        key: "uv_index",
+        name: "UV index",
+        value: "today.uv_index",
+        unit: None,
+        device_class: None,
+    },
+    Sensor {
+        key: "weather",
+        name: "Weather",
+        value: "current.weather_type_text",
+        unit: None,
+        device_class: None,
+    },
+];
+
+fn status_topic(settings: &MqttConfig) -> String {
+    format!("{}/status", settings.topic_prefix)
+}
+
+/// The discovery configs, conditions for the hour under way and daily forecast for one location.
+///
+/// Everything is retained, so Home Assistant picks the sensors up whenever it starts.
+pub(crate) fn messages(
+    settings: &MqttConfig,
+    forecast: &WeatherForecastResponse,
+    now: DateTime<Utc>,
+) -> Result<Vec<Message>, Error> {
+    let location = &forecast.location;
+    let today = forecast
+        .forecasts
+        .first()
+        .ok_or("The forecast has no days")?;
+    let current = forecast
+        .current_report(now)
+        .ok_or("The forecast has no hourly reports")?;
+
+    let base = format!("{}/{}", settings.topic_prefix, location.id);
+    let state_topic = format!("{}/state", base);
+    let device = json!({
+        "identifiers": [format!("weathema_{}", location.id)],
+        "name": format!("Weather {}", location.name),
+        "manufacturer": "weathema",
+        "model": "BBC Weather forecast",
+    });
+
+    let mut messages = Vec::with_capacity(SENSORS.len() + 2);
+    for sensor in &SENSORS {
+        let unique_id = format!("weathema_{}_{}", location.id, sensor.key);
+        let mut config = json!({
+            "name": sensor.name,
+            "unique_id": unique_id,
+            "state_topic": state_topic,
+            "value_template": format!("{{{{ value_json.{} }}}}", sensor.value),
+            "availability_topic": status_topic(settings),
+            "json_attributes_topic": if sensor.key == "weather" {
+                Some(format!("{}/forecast", base))
+            } else {
+                None
+            },
+            "device": device,
+        });
+        let fields = config.as_object_mut().expect("config is an object");
+        fields.retain(|_, value| !value.is_null());
+        if let Some(unit) = sensor.unit {
+            fields.insert("unit_of_measurement".into(), unit.into());
+            fields.insert("state_class".into(), "measurement".into());
+        }
+        if let Some(device_class) = sensor.device_class {
+            fields.insert("device_class".into(), device_class.into());
+        }
+
+        messages.push(Message {
+            topic: format!("{}/sensor/{}/config", settings.discovery_prefix, unique_id),
+            payload: serde_json::to_string(&config)?,
+        });
+    }
+
+    messages.push(Message {
+        topic: state_topic,
+        payload: serde_json::to_string(&StatePayload {
+            location: LocationOutput::from(location),
+            current: HourOutput::from(current),
+            today: DayOutput::from(&today.summary.report),
+        })?,
+    });
+    messages.push(Message {
+        topic: format!("{}/forecast", base),
+        payload: serde_json::to_string(&DailyDocument::new(forecast, forecast.forecasts.len()))?,
+    });
+
+    Ok(messages)
+}
+
+fn options(settings: &MqttConfig) -> MqttOptions {
+    let mut options = MqttOptions::new(&settings.client_id, &settings.host, settings.port);
+    options
+        .set_keep_alive(Duration::from_secs(30))
+        .set_max_packet_size(MAX_PACKET_SIZE, MAX_PACKET_SIZE)
+        .set_last_will(LastWill::new(
+            status_topic(settings),
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+    if let Some(username) = &settings.username {
+        options.set_credentials(username, settings.password.as_deref().unwrap_or_default());
+    }
+    options
+}
+
+pub(crate) async fn publish(client: &AsyncClient, messages: Vec<Message>) -> Result<(), Error> {
+    for message in messages {
+        client
+            .publish(message.topic, QoS::AtLeastOnce, true, message.payload)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Publishes the weather for `locations` every `settings.interval` seconds until interrupted, or
+/// just the once.
+pub(crate) async fn run(
+    weather_api: WeatherAPI,
+    locations: Vec<String>,
+    settings: MqttConfig,
+    once: bool,
+) -> Result<(), Error> {
+    if locations.is_empty() {
+        return Err(
+            "No locations to publish, pass --location or list them in the config file".into(),
+        );
+    }
+
+    let (client, mut eventloop) = AsyncClient::new(options(&settings), 64);
+
+    let status = status_topic(&settings);
+    let connection_client = client.clone();
+    let connection = tokio::spawn(async move {
+        loop {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Incoming::ConnAck(_))) => {
+                    connection_client.try_publish(&status, QoS::AtLeastOnce, true, "online")?;
+                }
+                Ok(Event::Outgoing(Outgoing::Disconnect)) => return Ok::<_, Error>(()),
+                Ok(_) => {}
+                // Polling again reconnects, but a one-off run has nobody waiting for that
+                Err(err) if once => return Err(err.into()),
+                Err(err) => {
+                    eprintln!("MQTT connection error: {}", err);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    });
+
+    let mut interval = tokio::time::interval(Duration::from_secs(settings.interval.max(1)));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        let forecasts = futures::future::join_all(
+            locations
+                .iter()
+                .map(|location| weather_api.get_weather(location)),
+        )
+        .await;
+        for (location, forecast) in locations.iter().zip(forecasts) {
+            let published = match forecast {
+                Ok(forecast) => match messages(&settings, &forecast, Utc::now()) {
+                    Ok(messages) => publish(&client, messages).await,
+                    Err(err) => Err(err),
+                },
+                Err(err) => Err(err),
+            };
+            // One location failing shouldn't stop the others being kept up to date
+            match published {
+                Ok(()) => {}
+                Err(err) if once => return Err(err),
+                Err(err) => eprintln!("Couldn't publish {}: {}", location, err),
+            }
+        }
+
+        if once {
+            break;
+        }
+    }
+
+    if !once {
+        client
+            .publish(status_topic(&settings), QoS::AtLeastOnce, true, "offline")
+            .await?;
+    }
+    client.disconnect().await?;
+    connection.await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use chrono::TimeZone;
+    use serde_json::Value;
+
+    /// 15:00 at the location, the first hour of the forecast.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 8, 29, 14, 0, 0).unwrap()
+    }
+
+    fn find(messages: &[Message], topic: &str) -> Value {
+        let message = messages
+            .iter()
+            .find(|message| message.topic == topic)
+            .unwrap_or_else(|| panic!("no message on {}", topic));
+        serde_json::from_str(&message.payload).unwrap()
+    }
+
+    #[test]
+    fn publishes_discovery_configs() {
+        let messages = messages(&MqttConfig::default(), &test_forecast(), now()).unwrap();
+        assert_eq!(messages.len(), SENSORS.len() + 2);
+
+        let config = find(
+            &messages,
+            "homeassistant/sensor/weathema_2650584_temperature/config",
+        );
+        assert_eq!(config["state_topic"], "weathema/2650584/state");
+        assert_eq!(
+            config["value_template"],
+            "{{ value_json.current.temperature_c }}"
+        );
+        assert_eq!(config["unit_of_measurement"], "°C");
+        assert_eq!(config["device_class"], "temperature");
+        assert_eq!(config["availability_topic"], "weathema/status");
+        assert_eq!(config["device"]["identifiers"][0], "weathema_2650584");
+        assert!(config.get("json_attributes_topic").is_none());
+
+        let config = find(
+            &messages,
+            "homeassistant/sensor/weathema_2650584_wind_direction/config",
+        );
+        assert!(config.get("unit_of_measurement").is_none());
+        assert!(config.get("state_class").is_none());
+    }
+
+    #[test]
+    fn every_template_reads_a_state_field() {
+        let messages = messages(&MqttConfig::default(), &test_forecast(), now()).unwrap();
+        let state = find(&messages, "weathema/2650584/state");
+
+        for sensor in &SENSORS {
+            let value = sensor
+                .value
+                .split('.')
+                .fold(&state, |value, field| &value[field]);
+            assert!(!value.is_null(), "{}", sensor.value);
+        }
+        assert_eq!(state["location"]["name"], "Dyserth");
+        assert_eq!(state["current"]["temperature_c"], 18.0);
+        assert_eq!(state["today"]["max_temp_c"], 18.0);
+    }
+
+    #[test]
+    fn publishes_the_hour_under_way() {
+        // 21:00 at the location, six hours after the forecast starts
+        let evening = Utc.with_ymd_and_hms(2024, 8, 29, 20, 0, 0).unwrap();
+        let messages = messages(&MqttConfig::default(), &test_forecast(), evening).unwrap();
+        let state = find(&messages, "weathema/2650584/state");
+        assert_eq!(state["current"]["timeslot"], "21:00");
+        assert_eq!(state["current"]["temperature_c"], 14.0);
+    }
+
+    #[test]
+    fn publishes_the_daily_forecast() {
+        let messages = messages(&MqttConfig::default(), &test_forecast(), now()).unwrap();
+        let forecast = find(&messages, "weathema/2650584/forecast");
+        assert_eq!(forecast["days"].as_array().unwrap().len(), 14);
+    }
+
+    /// Needs a broker such as `mosquitto` listening on `WEATHEMA_TEST_MQTT_HOST` (default
+    /// `localhost:1883`), run with `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore]
+    async fn round_trips_through_a_local_broker() {
+        let address =
+            std::env::var("WEATHEMA_TEST_MQTT_HOST").unwrap_or("localhost:1883".to_string());
+        let (host, port) = address.rsplit_once(':').unwrap();
+        let settings = MqttConfig {
+            host: host.to_string(),
+            port: port.parse().unwrap(),
+            client_id: "weathema-test-publisher".to_string(),
+            topic_prefix: "weathema-test".to_string(),
+            discovery_prefix: "homeassistant-test".to_string(),
+            ..MqttConfig::default()
+        };
+
+        let mut subscriber_options =
+            MqttOptions::new("weathema-test-subscriber", &settings.host, settings.port);
+        subscriber_options.set_max_packet_size(MAX_PACKET_SIZE, MAX_PACKET_SIZE);
+        let (subscriber, mut subscriber_loop) = AsyncClient::new(subscriber_options, 16);
+        subscriber
+            .subscribe("weathema-test/#", QoS::AtLeastOnce)
+            .await
+            .unwrap();
+        loop {
+            if let Event::Incoming(Incoming::SubAck(_)) = subscriber_loop.poll().await.unwrap() {
+                break;
+            }
+        }
+
+        let (client, mut eventloop) = AsyncClient::new(options(&settings), 64);
+        tokio::spawn(async move { while eventloop.poll().await.is_ok() {} });
+        publish(
+            &client,
+            messages(&settings, &test_forecast(), now()).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Event::Incoming(Incoming::Publish(publish)) =
+                    subscriber_loop.poll().await.unwrap()
+                {
+                    if publish.topic == "weathema-test/2650584/state" {
+                        return publish.payload;
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+        let state: Value = serde_json::from_slice(&received).unwrap();
+        assert_eq!(state["location"]["id"], "2650584");
+    }
+}
@@ -0,0 +1,165 @@
+use super::template;
+use crate::client::{Units, WeatherForecastResponse, WeatherType};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+pub(crate) const DEFAULT_FORMAT: &str = "{icon} {temp}°{unit} {wind_dir}";
+
+/// The JSON waybar expects from a custom module with `"return-type": "json"`.
+#[derive(Serialize)]
+struct WaybarOutput {
+    text: String,
+    tooltip: String,
+    class: &'static str,
+    alt: &'static str,
+}
+
+fn class(weather_type: WeatherType) -> &'static str {
+    match weather_type {
+        WeatherType::Sunny => "sunny",
+        WeatherType::PartlyCloudy => "partly-cloudy",
+        WeatherType::Cloudy => "cloudy",
+        WeatherType::Rainy => "rainy",
+        WeatherType::Snowy => "snowy",
+        WeatherType::Stormy => "stormy",
+        WeatherType::Unknown => "unknown",
+    }
+}
+
+/// Looks up the value of a `{placeholder}` for today's forecast, or this hour's for `{temp}`.
+fn placeholder(
+    name: &str,
+    forecast: &WeatherForecastResponse,
+    units: Units,
+    now: DateTime<Utc>,
+) -> Option<String> {
+    let report = &forecast.forecasts.first()?.summary.report;
+    let weather_type = WeatherType::from_code(report.weather_type);
+
+    let value = match name {
+        "icon" => weather_type.icon().to_string(),
+        "text" => report.weather_type_text.clone(),
+        "temp" => {
+            let temperature = forecast
+                .current_report(now)
+                .map_or(report.max_temp_c, |hour| hour.temperature_c);
+            format!("{:.0}", units.temperature(temperature))
+        }
+        "max" => format!("{:.0}", units.temperature(report.max_temp_c)),
+        "min" => format!("{:.0}", units.temperature(report.min_temp_c)),
+        "unit" => units.temperature_symbol().to_string(),
+        "wind_dir" => report.wind_direction.clone(),
+        "wind_speed" => format!("{:.0}", units.speed(report.wind_speed_kph)),
+        "speed_unit" => units.speed_symbol().to_string(),
+        "rain" => format!("{:.0}", report.precipitation_probability_in_percent),
+        "location" => forecast.location.name.clone(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Replaces each known `{placeholder}` in `format`, anything else is copied through unchanged.
+pub(crate) fn render_line(
+    forecast: &WeatherForecastResponse,
+    format: &str,
+    units: Units,
+    now: DateTime<Utc>,
+) -> String {
+    template::fill(format, |name| placeholder(name, forecast, units, now))
+}
+
+fn tooltip(forecast: &WeatherForecastResponse, units: Units) -> String {
+    let Some(today) = forecast.forecasts.first() else {
+        return forecast.location.name.clone();
+    };
+    let report = &today.summary.report;
+    format!(
+        "{}\n{}\n{:.0}°{} to {:.0}°{}\nWind {:.0} {} {}\nRain {:.0}%\nSunrise {} Sunset {}",
+        forecast.location.name,
+        report.weather_type_text,
+        units.temperature(report.min_temp_c),
+        units.temperature_symbol(),
+        units.temperature(report.max_temp_c),
+        units.temperature_symbol(),
+        units.speed(report.wind_speed_kph),
+        units.speed_symbol(),
+        report.wind_direction,
+        report.precipitation_probability_in_percent,
+        report.sunrise.as_deref().unwrap_or("--:--"),
+        report.sunset.as_deref().unwrap_or("--:--")
+    )
+}
+
+pub(crate) fn render_waybar(
+    forecast: &WeatherForecastResponse,
+    format: &str,
+    units: Units,
+    now: DateTime<Utc>,
+) -> Result<String, serde_json::Error> {
+    let weather_type = forecast
+        .forecasts
+        .first()
+        .map(|today| WeatherType::from_code(today.summary.report.weather_type))
+        .unwrap_or(WeatherType::Unknown);
+
+    serde_json::to_string(&WaybarOutput {
+        text: render_line(forecast, format, units, now),
+        tooltip: tooltip(forecast, units),
+        class: class(weather_type),
+        alt: class(weather_type),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use chrono::TimeZone;
+
+    /// 21:00 at the location, when it is 14°C.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 8, 29, 20, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn renders_default_format() {
+        assert_eq!(
+            render_line(&test_forecast(), DEFAULT_FORMAT, Units::Metric, now()),
+            "⛅ 14°C W"
+        );
+    }
+
+    #[test]
+    fn renders_every_placeholder() {
+        assert_eq!(
+            render_line(
+                &test_forecast(),
+                "{location}: {text} {min}-{max}°{unit} {wind_speed}{speed_unit} {rain}%",
+                Units::Imperial,
+                now()
+            ),
+            "Dyserth: Sunny Intervals 48-64°F 12mph 0%"
+        );
+    }
+
+    #[test]
+    fn leaves_unknown_placeholders() {
+        assert_eq!(
+            render_line(&test_forecast(), "{nope} {temp} {", Units::Metric, now()),
+            "{nope} 14 {"
+        );
+    }
+
+    #[test]
+    fn renders_waybar_json() {
+        let output = render_waybar(&test_forecast(), "{temp}", Units::Metric, now()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json["text"], "14");
+        assert_eq!(json["class"], "partly-cloudy");
+        assert!(json["tooltip"]
+            .as_str()
+            .unwrap()
+            .starts_with("Dyserth\nSunny Intervals\n9°C to 18°C"));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::client::{sun, Units, WeatherForecastResponse};
+use chrono::FixedOffset;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Formats a local date and `HH:MM` time as an ISO 8601 date-time with the location's UTC offset.
+fn iso_local(date: &str, time: &str, offset: FixedOffset) -> String {
+    format!("{}T{}:00{}", date, time, offset)
+}
+
+/// Empty when the forecast leaves the time out.
+fn optional_time(date: &str, time: Option<&str>, offset: FixedOffset) -> String {
+    time.map(|time| iso_local(date, time, offset))
+        .unwrap_or_default()
+}
+
+fn optional(value: Option<f64>) -> String {
+    value.map(round).unwrap_or_default()
+}
+
+fn round(value: f64) -> String {
+    ((value * 10.0).round() / 10.0).to_string()
+}
+
+pub(crate) fn write_daily_csv(
+    forecast: &WeatherForecastResponse,
+    units: Units,
+    writer: impl Write,
+) -> Result<(), ::csv::Error> {
+    let temperature = units.temperature_symbol().to_lowercase();
+    let speed = units.speed_symbol();
+    let offset = sun::offset(forecast);
+
+    let mut writer = ::csv::Writer::from_writer(writer);
+    writer.write_record([
+        "date".to_string(),
+        "weather_type".to_string(),
+        "weather_type_text".to_string(),
+        format!("min_temp_{}", temperature),
+        format!("max_temp_{}", temperature),
+        format!("wind_speed_{}", speed),
+        "wind_direction".to_string(),
+        "precipitation_probability_percent".to_string(),
+        "sunrise".to_string(),
+        "sunset".to_string(),
+        "uv_index".to_string(),
+        "uv_index_text".to_string(),
+        "pollen_index".to_string(),
+        "pollen_index_text".to_string(),
+        "pollution_index".to_string(),
+        "pollution_index_text".to_string(),
+    ])?;
+
+    for day in &forecast.forecasts {
+        let report = &day.summary.report;
+        let mut record = vec![
+            report.local_date.clone(),
+            report.weather_type.to_string(),
+            report.weather_type_text.clone(),
+            round(units.temperature(report.min_temp_c)),
+            round(units.temperature(report.max_temp_c)),
+            round(units.speed(report.wind_speed_kph)),
+            report.wind_direction.clone(),
+            round(report.precipitation_probability_in_percent),
+            optional_time(&report.local_date, report.sunrise.as_deref(), offset),
+            optional_time(&report.local_date, report.sunset.as_deref(), offset),
+        ];
+        // Left empty on days they aren't forecast for
+        for index in report.health_indices() {
+            record.push(optional(index.value));
+            record.push(index.text.unwrap_or_default().to_string());
+        }
+        writer.write_record(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub(crate) fn write_hourly_csv(
+    forecast: &WeatherForecastResponse,
+    units: Units,
+    writer: impl Write,
+) -> Result<(), ::csv::Error> {
+    let temperature = units.temperature_symbol().to_lowercase();
+    let speed = units.speed_symbol();
+    let offset = sun::offset(forecast);
+
+    let mut writer = ::csv::Writer::from_writer(writer);
+    writer.write_record([
+        "time".to_string(),
+        "weather_type".to_string(),
+        "weather_type_text".to_string(),
+        format!("temperature_{}", temperature),
+        format!("feels_like_temperature_{}", temperature),
+        format!("wind_speed_{}", speed),
+        format!("gust_speed_{}", speed),
+        "wind_direction".to_string(),
+        "precipitation_probability_percent".to_string(),
+        "humidity_percent".to_string(),
+        "pressure_mb".to_string(),
+    ])?;
+
+    for day in &forecast.forecasts {
+        for report in &day.detailed.reports {
+            writer.write_record([
+                iso_local(&report.local_date, &report.timeslot, offset),
+                report.weather_type.to_string(),
+                report.weather_type_text.clone(),
+                round(units.temperature(report.temperature_c)),
+                round(units.temperature(report.feels_like_c())),
+                round(units.speed(report.wind_speed_kph)),
+                optional(report.gust_speed_kph.map(|gust| units.speed(gust))),
+                report.wind_direction.clone(),
+                round(report.precipitation_probability_in_percent),
+                optional(report.humidity),
+                optional(report.pressure),
+            ])?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Turns a place name into something safe to use in a file name.
+pub(crate) fn file_stem(forecast: &WeatherForecastResponse) -> String {
+    let stem = forecast
+        .location
+        .name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if stem.is_empty() {
+        forecast.location.id.clone()
+    } else {
+        stem
+    }
+}
+
+/// Writes `<location>-daily.csv` and `<location>-hourly.csv` into `dir`, returning their paths.
+pub(crate) fn export_csv(
+    forecast: &WeatherForecastResponse,
+    units: Units,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error + Send + Sync>> {
+    let stem = file_stem(forecast);
+
+    let daily = dir.join(format!("{}-daily.csv", stem));
+    write_daily_csv(forecast, units, std::fs::File::create(&daily)?)?;
+
+    let hourly = dir.join(format!("{}-hourly.csv", stem));
+    write_hourly_csv(forecast, units, std::fs::File::create(&hourly)?)?;
+
+    Ok(vec![daily, hourly])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+
+    #[test]
+    fn writes_daily_csv() {
+        let mut output = vec![];
+        write_daily_csv(&test_forecast(), Units::Metric, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+
+        assert_eq!(lines.len(), 15);
+        assert_eq!(
+            lines[0],
+            "date,weather_type,weather_type_text,min_temp_c,max_temp_c,wind_speed_kph,\
+             wind_direction,precipitation_probability_percent,sunrise,sunset,uv_index,\
+             uv_index_text,pollen_index,pollen_index_text,pollution_index,pollution_index_text"
+        );
+        assert_eq!(
+            lines[1],
+            "2024-08-29,3,Sunny Intervals,9,18,19,W,0,2024-08-29T06:19:00+01:00,\
+             2024-08-29T20:09:00+01:00,5,Moderate,4,Moderate,2,Low"
+        );
+        assert!(lines[6].ends_with(",3,Moderate,,,,"));
+    }
+
+    #[test]
+    fn daily_csv_uses_units() {
+        let mut output = vec![];
+        write_daily_csv(&test_forecast(), Units::Imperial, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+
+        assert!(lines[0].contains("min_temp_f,max_temp_f,wind_speed_mph"));
+        assert!(lines[1].starts_with("2024-08-29,3,Sunny Intervals,48.2,64.4,11.8,W,"));
+    }
+
+    #[test]
+    fn writes_hourly_csv() {
+        let mut output = vec![];
+        write_hourly_csv(&test_forecast(), Units::Metric, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "time,weather_type,weather_type_text,temperature_c,feels_like_temperature_c,\
+             wind_speed_kph,gust_speed_kph,wind_direction,precipitation_probability_percent,\
+             humidity_percent,pressure_mb"
+        );
+        assert_eq!(
+            lines[1],
+            "2024-08-29T15:00:00+01:00,3,Sunny Intervals,18,18,17,35,W,0,61,1017"
+        );
+        assert_eq!(lines.len(), 1 + 15 + 13 * 24);
+    }
+
+    #[test]
+    fn hourly_csv_uses_units() {
+        let mut output = vec![];
+        write_hourly_csv(&test_forecast(), Units::Imperial, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+
+        assert!(lines[0]
+            .contains("temperature_f,feels_like_temperature_f,wind_speed_mph,gust_speed_mph"));
+        assert!(lines[1].starts_with("2024-08-29T15:00:00+01:00,3,Sunny Intervals,64.4,64.4,10.6,"));
+    }
+}
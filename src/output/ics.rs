@@ -0,0 +1,256 @@
+use crate::client::{Units, WeatherForecastResponse};
+use crate::output::csv::file_stem;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use std::path::{Path, PathBuf};
+
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Escapes a TEXT value as described in RFC 5545 section 3.3.11.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends a content line, folding it so that no line is longer than 75 octets.
+fn push_line(output: &mut String, line: &str) {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_OCTETS {
+            output.push_str("\r\n ");
+            // The leading space of the continuation counts towards its length
+            length = 1;
+        }
+        output.push(c);
+        length += c.len_utf8();
+    }
+    output.push_str("\r\n");
+}
+
+/// Renders one all-day event per forecast day.
+///
+/// The UID is made from the location id and date so importing a newer export updates the events
+/// instead of adding duplicates. The SEQUENCE counts minutes up to when the day's forecast was
+/// last updated, so it grows with every newer forecast as calendars expect of a revised event.
+pub(crate) fn render_ics(
+    forecast: &WeatherForecastResponse,
+    units: Units,
+    now: DateTime<Utc>,
+) -> String {
+    let mut output = String::new();
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+
+    push_line(&mut output, "BEGIN:VCALENDAR");
+    push_line(&mut output, "VERSION:2.0");
+    push_line(&mut output, "PRODID:-//weathema//weathema//EN");
+    push_line(&mut output, "CALSCALE:GREGORIAN");
+    push_line(&mut output, "METHOD:PUBLISH");
+    push_line(
+        &mut output,
+        &format!(
+            "X-WR-CALNAME:{}",
+            escape(&format!("Weather for {}", forecast.location.name))
+        ),
+    );
+
+    let location = match forecast.location.container.as_str() {
+        "" => forecast.location.name.clone(),
+        container => format!("{}, {}", forecast.location.name, container),
+    };
+
+    for day in &forecast.forecasts {
+        let report = &day.summary.report;
+        let Ok(date) = NaiveDate::parse_from_str(&report.local_date, "%Y-%m-%d") else {
+            continue;
+        };
+        let Some(end) = date.checked_add_days(Days::new(1)) else {
+            continue;
+        };
+        let updated = DateTime::parse_from_rfc3339(&day.summary.last_updated)
+            .map_or(now, |updated| updated.with_timezone(&Utc));
+
+        let summary = format!(
+            "{} {:.0}°{} to {:.0}°{}, {:.0}% rain",
+            report.weather_type_text,
+            units.temperature(report.min_temp_c),
+            units.temperature_symbol(),
+            units.temperature(report.max_temp_c),
+            units.temperature_symbol(),
+            report.precipitation_probability_in_percent
+        );
+        let mut description = format!(
+            "Sunrise {}\nSunset {}\nWind {:.0} {} {}",
+            report.sunrise.as_deref().unwrap_or("--:--"),
+            report.sunset.as_deref().unwrap_or("--:--"),
+            units.speed(report.wind_speed_kph),
+            units.speed_symbol(),
+            report.wind_direction
+        );
+        for index in report.health_indices() {
+            if index.value.is_some() {
+                description.push_str(&format!("\n{} {}", index.name, index.describe()));
+            }
+        }
+
+        push_line(&mut output, "BEGIN:VEVENT");
+        push_line(
+            &mut output,
+            &format!(
+                "UID:{}-{}@weathema",
+                forecast.location.id,
+                date.format("%Y%m%d")
+            ),
+        );
+        push_line(&mut output, &format!("DTSTAMP:{}", stamp));
+        push_line(
+            &mut output,
+            &format!("LAST-MODIFIED:{}", updated.format("%Y%m%dT%H%M%SZ")),
+        );
+        push_line(
+            &mut output,
+            &format!("SEQUENCE:{}", updated.timestamp() / 60),
+        );
+        push_line(
+            &mut output,
+            &format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+        );
+        push_line(
+            &mut output,
+            &format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+        );
+        push_line(&mut output, &format!("SUMMARY:{}", escape(&summary)));
+        push_line(
+            &mut output,
+            &format!("DESCRIPTION:{}", escape(&description)),
+        );
+        push_line(&mut output, &format!("LOCATION:{}", escape(&location)));
+        push_line(&mut output, "TRANSP:TRANSPARENT");
+        push_line(&mut output, "END:VEVENT");
+    }
+
+    push_line(&mut output, "END:VCALENDAR");
+    output
+}
+
+/// Writes `<location>.ics` into `dir`, returning its path.
+pub(crate) fn export_ics(
+    forecast: &WeatherForecastResponse,
+    units: Units,
+    dir: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+    let path = dir.join(format!("{}.ics", file_stem(forecast)));
+    std::fs::write(&path, render_ics(forecast, units, Utc::now()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use chrono::TimeZone;
+
+    fn render(now: DateTime<Utc>) -> String {
+        render_ics(&test_forecast(), Units::Metric, now)
+    }
+
+    #[test]
+    fn renders_an_all_day_event_per_day() {
+        let output = render(Utc.with_ymd_and_hms(2024, 8, 29, 14, 0, 0).unwrap());
+
+        assert!(output.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(output.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(output.matches("BEGIN:VEVENT").count(), 14);
+        assert!(output.contains(
+            "BEGIN:VEVENT\r\n\
+             UID:2650584-20240829@weathema\r\n\
+             DTSTAMP:20240829T140000Z\r\n\
+             LAST-MODIFIED:20240829T130018Z\r\n\
+             SEQUENCE:28748940\r\n\
+             DTSTART;VALUE=DATE:20240829\r\n\
+             DTEND;VALUE=DATE:20240830\r\n\
+             SUMMARY:Sunny Intervals 9°C to 18°C\\, 0% rain\r\n\
+             DESCRIPTION:Sunrise 06:19\\nSunset 20:09\\nWind 19 kph W\\nUV 5 Moderate\\nPoll\r\n \
+             en 4 Moderate\\nPollution 2 Low\r\n\
+             LOCATION:Dyserth\\, Denbighshire\r\n"
+        ));
+    }
+
+    #[test]
+    fn newer_forecasts_have_a_higher_sequence() {
+        let sequences = |output: &str| {
+            output
+                .lines()
+                .filter_map(|line| line.strip_prefix("SEQUENCE:"))
+                .map(|sequence| sequence.parse::<i64>().unwrap())
+                .collect::<Vec<_>>()
+        };
+        let now = Utc.with_ymd_and_hms(2024, 8, 29, 18, 0, 0).unwrap();
+        let first = render(now);
+
+        let mut forecast = test_forecast();
+        for day in &mut forecast.forecasts {
+            day.summary.last_updated = "2024-08-29T17:00:00+01:00".to_string();
+        }
+        let second = render_ics(&forecast, Units::Metric, now);
+
+        assert!(sequences(&first)
+            .iter()
+            .zip(sequences(&second))
+            .all(|(first, second)| *first < second));
+        // Re-exporting the same forecast leaves the events as they were
+        assert_eq!(sequences(&first), sequences(&render(now)));
+    }
+
+    #[test]
+    fn leaves_out_an_empty_container() {
+        let mut forecast = test_forecast();
+        forecast.location.container.clear();
+        let output = render_ics(&forecast, Units::Metric, Utc::now());
+
+        assert!(output.contains("LOCATION:Dyserth\r\n"));
+    }
+
+    #[test]
+    fn uids_are_stable_between_exports() {
+        let uids = |output: &str| {
+            output
+                .lines()
+                .filter(|line| line.starts_with("UID:"))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        let first = render(Utc.with_ymd_and_hms(2024, 8, 29, 14, 0, 0).unwrap());
+        let second = render(Utc.with_ymd_and_hms(2024, 8, 29, 18, 0, 0).unwrap());
+
+        assert_ne!(first, second);
+        assert_eq!(uids(&first), uids(&second));
+    }
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(escape("a,b;c\\d\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let mut output = String::new();
+        push_line(&mut output, &format!("SUMMARY:{}", "é".repeat(60)));
+
+        for line in output.split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(
+            output.replace("\r\n ", ""),
+            format!("SUMMARY:{}\r\n", "é".repeat(60))
+        );
+    }
+}
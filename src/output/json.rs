@@ -0,0 +1,312 @@
+use crate::client::visibility::Visibility;
+use crate::client::{
+    WeatherDetailedReport, WeatherForecastLocation, WeatherForecastResponse, WeatherLocationResult,
+    WeatherSummaryReport,
+};
+use serde::Serialize;
+
+/// Bumped whenever a field is renamed or removed, adding fields does not change the version but
+/// is listed under Changes in docs/json-output.md.
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+/// The `--format json` document, see docs/json-output.md.
+#[derive(Serialize)]
+pub(crate) struct ForecastDocument<'a> {
+    pub schema_version: u32,
+    pub location: LocationOutput<'a>,
+    pub days: Vec<DayDocument<'a>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct DayDocument<'a> {
+    #[serde(flatten)]
+    pub summary: DayOutput<'a>,
+    pub hourly: Vec<HourOutput<'a>>,
+}
+
+/// A single `--format ndjson` line, one per day.
+#[derive(Serialize)]
+struct DayRecord<'a> {
+    schema_version: u32,
+    location: LocationOutput<'a>,
+    day: DayDocument<'a>,
+}
+
+/// The daily forecasts served by `weathema serve`.
+#[derive(Serialize)]
+pub(crate) struct DailyDocument<'a> {
+    pub schema_version: u32,
+    pub location: LocationOutput<'a>,
+    pub days: Vec<DayOutput<'a>>,
+}
+
+impl<'a> DailyDocument<'a> {
+    pub fn new(forecast: &'a WeatherForecastResponse, days: usize) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            location: LocationOutput::from(&forecast.location),
+            days: forecast
+                .forecasts
+                .iter()
+                .take(days)
+                .map(|day| DayOutput::from(&day.summary.report))
+                .collect(),
+        }
+    }
+}
+
+/// The hourly reports served by `weathema serve`.
+#[derive(Serialize)]
+pub(crate) struct HourlyDocument<'a> {
+    pub schema_version: u32,
+    pub location: LocationOutput<'a>,
+    pub hours: Vec<HourOutput<'a>>,
+}
+
+impl<'a> HourlyDocument<'a> {
+    pub fn new(forecast: &'a WeatherForecastResponse, days: usize) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            location: LocationOutput::from(&forecast.location),
+            hours: forecast
+                .forecasts
+                .iter()
+                .take(days)
+                .flat_map(|day| &day.detailed.reports)
+                .map(HourOutput::from)
+                .collect(),
+        }
+    }
+}
+
+/// The location search results served by `weathema serve`.
+#[derive(Serialize)]
+pub(crate) struct LocationsDocument<'a> {
+    pub schema_version: u32,
+    pub results: Vec<LocationResultOutput<'a>>,
+}
+
+impl<'a> LocationsDocument<'a> {
+    pub fn new(results: &'a [WeatherLocationResult]) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            results: results.iter().map(LocationResultOutput::from).collect(),
+        }
+    }
+}
+
+// The output types below are the documented schema, kept apart from the provider's responses so
+// that a change in what the BBC sends can't change the output by accident
+
+#[derive(Serialize)]
+pub(crate) struct LocationOutput<'a> {
+    id: &'a str,
+    name: &'a str,
+    container: &'a str,
+    latitude: f64,
+    longitude: f64,
+}
+
+impl<'a> From<&'a WeatherForecastLocation> for LocationOutput<'a> {
+    fn from(location: &'a WeatherForecastLocation) -> Self {
+        Self {
+            id: &location.id,
+            name: &location.name,
+            container: &location.container,
+            latitude: location.latitude,
+            longitude: location.longitude,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct LocationResultOutput<'a> {
+    id: &'a str,
+    name: &'a str,
+    container: &'a str,
+    country: &'a str,
+    latitude: f64,
+    longitude: f64,
+}
+
+impl<'a> From<&'a WeatherLocationResult> for LocationResultOutput<'a> {
+    fn from(result: &'a WeatherLocationResult) -> Self {
+        Self {
+            id: &result.id,
+            name: &result.name,
+            container: &result.container,
+            country: &result.country,
+            latitude: result.latitude,
+            longitude: result.longitude,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct DayOutput<'a> {
+    local_date: &'a str,
+    sunrise: Option<&'a str>,
+    sunset: Option<&'a str>,
+    max_temp_c: f64,
+    min_temp_c: f64,
+    lower_max_temp_c: Option<f64>,
+    upper_max_temp_c: Option<f64>,
+    lower_min_temp_c: Option<f64>,
+    upper_min_temp_c: Option<f64>,
+    most_likely_high_temp_c: Option<f64>,
+    most_likely_low_temp_c: Option<f64>,
+    wind_speed_kph: f64,
+    gust_speed_kph: Option<f64>,
+    wind_direction: &'a str,
+    weather_type: u8,
+    weather_type_text: &'a str,
+    precipitation_probability_in_percent: f64,
+    uv_index: Option<f64>,
+    uv_index_band: Option<&'a str>,
+    uv_index_text: Option<&'a str>,
+    pollen_index: Option<f64>,
+    pollen_index_band: Option<&'a str>,
+    pollen_index_text: Option<&'a str>,
+    pollution_index: Option<f64>,
+    pollution_index_band: Option<&'a str>,
+    pollution_index_text: Option<&'a str>,
+}
+
+impl<'a> From<&'a WeatherSummaryReport> for DayOutput<'a> {
+    fn from(report: &'a WeatherSummaryReport) -> Self {
+        Self {
+            local_date: &report.local_date,
+            sunrise: report.sunrise.as_deref(),
+            sunset: report.sunset.as_deref(),
+            max_temp_c: report.max_temp_c,
+            min_temp_c: report.min_temp_c,
+            lower_max_temp_c: report.lower_max_temp_c,
+            upper_max_temp_c: report.upper_max_temp_c,
+            lower_min_temp_c: report.lower_min_temp_c,
+            upper_min_temp_c: report.upper_min_temp_c,
+            most_likely_high_temp_c: report.most_likely_high_temp_c,
+            most_likely_low_temp_c: report.most_likely_low_temp_c,
+            wind_speed_kph: report.wind_speed_kph,
+            gust_speed_kph: report.gust_speed_kph,
+            wind_direction: &report.wind_direction,
+            weather_type: report.weather_type,
+            weather_type_text: &report.weather_type_text,
+            precipitation_probability_in_percent: report.precipitation_probability_in_percent,
+            uv_index: report.uv_index,
+            uv_index_band: report.uv_index_band.as_deref(),
+            uv_index_text: report.uv_index_text.as_deref(),
+            pollen_index: report.pollen_index,
+            pollen_index_band: report.pollen_index_band.as_deref(),
+            pollen_index_text: report.pollen_index_text.as_deref(),
+            pollution_index: report.pollution_index,
+            pollution_index_band: report.pollution_index_band.as_deref(),
+            pollution_index_text: report.pollution_index_text.as_deref(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct HourOutput<'a> {
+    local_date: &'a str,
+    timeslot: &'a str,
+    weather_type: u8,
+    weather_type_text: &'a str,
+    temperature_c: f64,
+    feels_like_temperature_c: f64,
+    wind_speed_kph: f64,
+    gust_speed_kph: Option<f64>,
+    wind_direction: &'a str,
+    precipitation_probability_in_percent: f64,
+    humidity: Option<f64>,
+    pressure: Option<f64>,
+    visibility: Option<&'static str>,
+}
+
+impl<'a> From<&'a WeatherDetailedReport> for HourOutput<'a> {
+    fn from(report: &'a WeatherDetailedReport) -> Self {
+        Self {
+            local_date: &report.local_date,
+            timeslot: &report.timeslot,
+            weather_type: report.weather_type,
+            weather_type_text: &report.weather_type_text,
+            temperature_c: report.temperature_c,
+            feels_like_temperature_c: report.feels_like_c(),
+            wind_speed_kph: report.wind_speed_kph,
+            gust_speed_kph: report.gust_speed_kph,
+            wind_direction: &report.wind_direction,
+            precipitation_probability_in_percent: report.precipitation_probability_in_percent,
+            humidity: report.humidity,
+            pressure: report.pressure,
+            visibility: report.visibility.map(Visibility::name),
+        }
+    }
+}
+
+fn days(forecast: &WeatherForecastResponse, days: usize) -> impl Iterator<Item = DayDocument<'_>> {
+    forecast.forecasts.iter().take(days).map(|day| DayDocument {
+        summary: DayOutput::from(&day.summary.report),
+        hourly: day.detailed.reports.iter().map(HourOutput::from).collect(),
+    })
+}
+
+pub(crate) fn render_json(
+    forecast: &WeatherForecastResponse,
+    day_count: usize,
+) -> Result<String, serde_json::Error> {
+    let document = ForecastDocument {
+        schema_version: SCHEMA_VERSION,
+        location: LocationOutput::from(&forecast.location),
+        days: days(forecast, day_count).collect(),
+    };
+    let mut output = serde_json::to_string_pretty(&document)?;
+    output.push('\n');
+    Ok(output)
+}
+
+pub(crate) fn render_ndjson(
+    forecast: &WeatherForecastResponse,
+    day_count: usize,
+) -> Result<String, serde_json::Error> {
+    let mut output = String::new();
+    for day in days(forecast, day_count) {
+        let record = DayRecord {
+            schema_version: SCHEMA_VERSION,
+            location: LocationOutput::from(&forecast.location),
+            day,
+        };
+        output.push_str(&serde_json::to_string(&record)?);
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+
+    #[test]
+    fn json_matches_snapshot() {
+        let output = render_json(&test_forecast(), 2).unwrap();
+        let snapshot = std::fs::read_to_string("src/test_data/snapshots/forecast.json").unwrap();
+        assert_eq!(output, snapshot);
+    }
+
+    #[test]
+    fn ndjson_matches_snapshot() {
+        let output = render_ndjson(&test_forecast(), 2).unwrap();
+        let snapshot = std::fs::read_to_string("src/test_data/snapshots/forecast.ndjson").unwrap();
+        assert_eq!(output, snapshot);
+    }
+
+    #[test]
+    fn ndjson_has_one_record_per_day() {
+        let output = render_ndjson(&test_forecast(), 14).unwrap();
+        assert_eq!(output.lines().count(), 14);
+        for line in output.lines() {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(record["schema_version"], SCHEMA_VERSION);
+            assert_eq!(record["location"]["name"], "Dyserth");
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub(crate) mod bar;
+pub(crate) mod csv;
+pub(crate) mod ics;
+pub(crate) mod json;
+pub(crate) mod template;
+pub(crate) mod text;
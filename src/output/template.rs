@@ -0,0 +1,26 @@
+/// Replaces each `{placeholder}` that `lookup` knows, anything else is copied through unchanged.
+pub(crate) fn fill(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest
+            .find('}')
+            .and_then(|end| Some((end, lookup(&rest[1..end])?)));
+        match value {
+            Some((end, value)) => {
+                output.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
@@ -0,0 +1,224 @@
+use crate::client::{Units, WeatherForecastResponse, WeatherSummaryReport, WeatherType};
+use std::fmt::Write;
+use std::fs::read_to_string;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const MAGENTA: &str = "\x1b[35m";
+const CYAN: &str = "\x1b[36m";
+const WHITE: &str = "\x1b[37m";
+
+pub(crate) struct TextOptions {
+    pub units: Units,
+    pub colour: bool,
+    pub art: bool,
+}
+
+impl TextOptions {
+    fn paint(&self, text: &str, colour: &str) -> String {
+        if self.colour && !colour.is_empty() {
+            format!("{}{}{}", colour, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn temperature(&self, celsius: f64) -> String {
+        format!(
+            "{:.0}°{}",
+            self.units.temperature(celsius),
+            self.units.temperature_symbol()
+        )
+    }
+
+    fn wind(&self, report: &WeatherSummaryReport) -> String {
+        format!(
+            "{:.0} {} {}",
+            self.units.speed(report.wind_speed_kph),
+            self.units.speed_symbol(),
+            report.wind_direction
+        )
+    }
+}
+
+fn weather_colour(weather_type: WeatherType) -> &'static str {
+    match weather_type {
+        WeatherType::Sunny | WeatherType::PartlyCloudy => YELLOW,
+        WeatherType::Cloudy => WHITE,
+        WeatherType::Rainy => BLUE,
+        WeatherType::Snowy => CYAN,
+        WeatherType::Stormy => MAGENTA,
+        WeatherType::Unknown => "",
+    }
+}
+
+fn location_title(forecast: &WeatherForecastResponse) -> String {
+    if forecast.location.container.is_empty() {
+        forecast.location.name.clone()
+    } else {
+        format!(
+            "{}, {}",
+            forecast.location.name, forecast.location.container
+        )
+    }
+}
+
+/// Renders today's conditions, optionally headed by the ASCII art for the weather type.
+pub(crate) fn render_now(forecast: &WeatherForecastResponse, options: &TextOptions) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "{}", options.paint(&location_title(forecast), BOLD));
+
+    let Some(today) = forecast.forecasts.first() else {
+        let _ = writeln!(output, "No forecast available");
+        return output;
+    };
+    let report = &today.summary.report;
+    let weather_type = WeatherType::from_code(report.weather_type);
+
+    if options.art {
+        let art = read_to_string(weather_type.image_path()).unwrap_or_default();
+        if !art.is_empty() {
+            let _ = writeln!(
+                output,
+                "{}",
+                options.paint(art.trim_end(), weather_colour(weather_type))
+            );
+        }
+    }
+
+    let weather_text = match today.detailed.reports.first() {
+        Some(now) => now.weather_type_text.as_str(),
+        None => report.weather_type_text.as_str(),
+    };
+    let _ = writeln!(
+        output,
+        "{}",
+        options.paint(weather_text, weather_colour(weather_type))
+    );
+    let _ = writeln!(
+        output,
+        "Temperature: {} to {}",
+        options.paint(&options.temperature(report.min_temp_c), BLUE),
+        options.paint(&options.temperature(report.max_temp_c), RED)
+    );
+    let _ = writeln!(output, "Wind:        {}", options.wind(report));
+    let _ = writeln!(
+        output,
+        "Rain:        {:.0}%",
+        report.precipitation_probability_in_percent
+    );
+    let _ = writeln!(
+        output,
+        "Sunrise:     {}  Sunset: {}",
+        report.sunrise.as_deref().unwrap_or("--:--"),
+        report.sunset.as_deref().unwrap_or("--:--")
+    );
+    output
+}
+
+/// Renders a table with one row per day for up to `days` days.
+pub(crate) fn render_forecast(
+    forecast: &WeatherForecastResponse,
+    days: usize,
+    options: &TextOptions,
+) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "{}", options.paint(&location_title(forecast), BOLD));
+    let _ = writeln!(
+        output,
+        "{}",
+        options.paint(
+            &format!(
+                "{:<12}{:<28}{:>7}{:>7}  {:<14}{:>5}",
+                "Date", "Weather", "Min", "Max", "Wind", "Rain"
+            ),
+            BOLD
+        )
+    );
+
+    for day in forecast.forecasts.iter().take(days) {
+        let report = &day.summary.report;
+        let weather_type = WeatherType::from_code(report.weather_type);
+        let _ = writeln!(
+            output,
+            "{:<12}{}{}{}  {:<14}{:>5}",
+            report.local_date,
+            options.paint(
+                &format!("{:<28}", report.weather_type_text),
+                weather_colour(weather_type)
+            ),
+            options.paint(
+                &format!("{:>7}", options.temperature(report.min_temp_c)),
+                BLUE
+            ),
+            options.paint(
+                &format!("{:>7}", options.temperature(report.max_temp_c)),
+                RED
+            ),
+            options.wind(report),
+            format!("{:.0}%", report.precipitation_probability_in_percent),
+        );
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+
+    #[test]
+    fn renders_now_without_colour() {
+        let options = TextOptions {
+            units: Units::Metric,
+            colour: false,
+            art: false,
+        };
+        let output = render_now(&test_forecast(), &options);
+
+        assert_eq!(
+            output,
+            "Dyserth, Denbighshire\n\
+             Sunny Intervals\n\
+             Temperature: 9°C to 18°C\n\
+             Wind:        19 kph W\n\
+             Rain:        0%\n\
+             Sunrise:     06:19  Sunset: 20:09\n"
+        );
+    }
+
+    #[test]
+    fn renders_forecast_table() {
+        let options = TextOptions {
+            units: Units::Imperial,
+            colour: false,
+            art: false,
+        };
+        let output = render_forecast(&test_forecast(), 2, &options);
+        let lines: Vec<_> = output.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("Date"));
+        assert!(lines[2].starts_with("2024-08-29  Sunny Intervals"));
+        assert!(lines[2].contains("48°F"));
+        assert!(lines[2].contains("64°F"));
+        assert!(lines[2].contains("12 mph W"));
+        assert!(lines[3].starts_with("2024-08-30"));
+    }
+
+    #[test]
+    fn colour_is_optional() {
+        let mut options = TextOptions {
+            units: Units::Metric,
+            colour: false,
+            art: false,
+        };
+        assert!(!render_forecast(&test_forecast(), 1, &options).contains('\x1b'));
+
+        options.colour = true;
+        assert!(render_forecast(&test_forecast(), 1, &options).contains(RED));
+    }
+}
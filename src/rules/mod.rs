@@ -0,0 +1,518 @@
+pub(crate) mod notify;
+pub(crate) mod severe;
+pub(crate) mod webhook;
+
+use crate::client::{WeatherDetailedReport, WeatherForecastResponse, WeatherSummaryReport};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+
+/// An alert rule from the `[[rules]]` tables of the config file, e.g.
+///
+/// ```toml
+/// [[rules]]
+/// name = "Rain tomorrow"
+/// when = "rain > 70"
+/// day = "tomorrow"
+/// webhook = "http://localhost:9000/hooks/weather"
+///
+/// [[rules]]
+/// name = "Rain soon"
+/// when = "rain >= 50"
+/// within = 2
+/// notify = true
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "RuleTable")]
+pub(crate) struct Rule {
+    pub name: String,
+    pub when: Condition,
+    pub day: Day,
+    /// Look at the hourly reports for the next few hours instead of the daily summaries
+    pub within: Option<usize>,
+    /// Where to POST the alert
+    pub webhook: Option<String>,
+    /// JSON body template, see `webhook::render_body`
+    pub body: Option<String>,
+    /// Send a desktop notification from `weathema watch`
+    pub notify: bool,
+}
+
+/// A rule as written in the config file, checked for settings that can't be used together.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleTable {
+    name: String,
+    when: Condition,
+    day: Option<Day>,
+    within: Option<usize>,
+    webhook: Option<String>,
+    body: Option<String>,
+    #[serde(default)]
+    notify: bool,
+}
+
+impl TryFrom<RuleTable> for Rule {
+    type Error = String;
+
+    fn try_from(table: RuleTable) -> Result<Self, Self::Error> {
+        if table.within.is_some() {
+            if table.day.is_some() {
+                return Err(format!(
+                    "Rule {:?} can't set both `within` and `day`",
+                    table.name
+                ));
+            }
+            if table.when.metric == Metric::Uv {
+                return Err(format!(
+                    "Rule {:?} can't use `uv` with `within`, the hourly reports have no UV index",
+                    table.name
+                ));
+            }
+        }
+
+        Ok(Rule {
+            name: table.name,
+            when: table.when,
+            day: table.day.unwrap_or_default(),
+            within: table.within,
+            webhook: table.webhook,
+            body: table.body,
+            notify: table.notify,
+        })
+    }
+}
+
+/// The forecast days a rule looks at.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Day {
+    #[default]
+    Today,
+    Tomorrow,
+    /// Every day in the forecast
+    Any,
+}
+
+/// A summary value compared against a threshold, written as `<metric> <op> <threshold>`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub(crate) struct Condition {
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Metric {
+    /// Chance of precipitation in percent
+    Rain,
+    /// °C
+    MinTemp,
+    /// °C
+    MaxTemp,
+    /// kph
+    Wind,
+    /// kph
+    Gust,
+    Uv,
+}
+
+const METRICS: [(&str, Metric); 6] = [
+    ("rain", Metric::Rain),
+    ("min_temp", Metric::MinTemp),
+    ("max_temp", Metric::MaxTemp),
+    ("wind", Metric::Wind),
+    ("gust", Metric::Gust),
+    ("uv", Metric::Uv),
+];
+
+impl Metric {
+    pub fn unit(self) -> &'static str {
+        match self {
+            Metric::Rain => "%",
+            Metric::MinTemp | Metric::MaxTemp => "°C",
+            Metric::Wind | Metric::Gust => " kph",
+            Metric::Uv => "",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        METRICS
+            .iter()
+            .find(|(_, metric)| *metric == self)
+            .map(|(name, _)| *name)
+            .expect("every metric is named")
+    }
+
+    /// The value for a day, if the provider forecast it.
+    fn summary_value(self, report: &WeatherSummaryReport) -> Option<f64> {
+        match self {
+            Metric::Rain => Some(report.precipitation_probability_in_percent),
+            Metric::MinTemp => Some(report.min_temp_c),
+            Metric::MaxTemp => Some(report.max_temp_c),
+            Metric::Wind => Some(report.wind_speed_kph),
+            Metric::Gust => report.gust_speed_kph,
+            Metric::Uv => report.uv_index,
+        }
+    }
+
+    /// The value for an hour, both temperature metrics compare the hour's temperature.
+    fn hourly_value(self, report: &WeatherDetailedReport) -> Option<f64> {
+        match self {
+            Metric::Rain => Some(report.precipitation_probability_in_percent),
+            Metric::MinTemp | Metric::MaxTemp => Some(report.temperature_c),
+            Metric::Wind => Some(report.wind_speed_kph),
+            Metric::Gust => report.gust_speed_kph,
+            Metric::Uv => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Comparison {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+const COMPARISONS: [(&str, Comparison); 4] = [
+    (">=", Comparison::AtLeast),
+    ("<=", Comparison::AtMost),
+    (">", Comparison::Above),
+    ("<", Comparison::Below),
+];
+
+impl Comparison {
+    fn symbol(self) -> &'static str {
+        COMPARISONS
+            .iter()
+            .find(|(_, comparison)| *comparison == self)
+            .map(|(symbol, _)| *symbol)
+            .expect("every comparison has a symbol")
+    }
+
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::AtLeast => value >= threshold,
+            Comparison::Below => value < threshold,
+            Comparison::AtMost => value <= threshold,
+        }
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = String;
+
+    fn try_from(condition: String) -> Result<Self, Self::Error> {
+        let invalid = || {
+            format!(
+                "Invalid condition {:?}, expected e.g. \"rain > 70\" with one of {}",
+                condition,
+                METRICS.map(|(name, _)| name).join(", ")
+            )
+        };
+
+        let (symbol, comparison) = COMPARISONS
+            .iter()
+            .find(|(symbol, _)| condition.contains(symbol))
+            .ok_or_else(invalid)?;
+        let (metric, threshold) = condition.split_once(symbol).ok_or_else(invalid)?;
+        let (_, metric) = METRICS
+            .iter()
+            .find(|(name, _)| *name == metric.trim())
+            .ok_or_else(invalid)?;
+        let threshold = threshold.trim().parse().map_err(|_| invalid())?;
+
+        Ok(Condition {
+            metric: *metric,
+            comparison: *comparison,
+            threshold,
+        })
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.metric.name(),
+            self.comparison.symbol(),
+            self.threshold
+        )
+    }
+}
+
+/// A rule that holds on one forecast day, or from one hour for rules looking `within` hours.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Alert {
+    pub rule: String,
+    pub condition: Condition,
+    pub date: String,
+    pub timeslot: Option<String>,
+    pub value: f64,
+}
+
+impl Rule {
+    /// The days of `forecast` on which this rule holds, or the hour it starts to hold within the
+    /// `within` hours after `now`.
+    pub fn evaluate(&self, forecast: &WeatherForecastResponse, now: DateTime<Utc>) -> Vec<Alert> {
+        if let Some(hours) = self.within {
+            return self.evaluate_hourly(forecast, hours, now);
+        }
+
+        let days = match self.day {
+            Day::Today => 0..1,
+            Day::Tomorrow => 1..2,
+            Day::Any => 0..forecast.forecasts.len(),
+        };
+
+        let days = forecast.forecasts.get(days).unwrap_or_default();
+        days.iter()
+            .filter_map(|day| {
+                let report = &day.summary.report;
+                let value = self.when.metric.summary_value(report)?;
+                self.when
+                    .comparison
+                    .holds(value, self.when.threshold)
+                    .then(|| Alert {
+                        rule: self.name.clone(),
+                        condition: self.when,
+                        date: report.local_date.clone(),
+                        timeslot: None,
+                        value,
+                    })
+            })
+            .collect()
+    }
+
+    /// Only a change counts, so the alert is for the first hour the condition holds after an hour
+    /// it didn't.
+    fn evaluate_hourly(
+        &self,
+        forecast: &WeatherForecastResponse,
+        hours: usize,
+        now: DateTime<Utc>,
+    ) -> Vec<Alert> {
+        let holds = |report: &WeatherDetailedReport| {
+            self.when
+                .metric
+                .hourly_value(report)
+                .filter(|value| self.when.comparison.holds(*value, self.when.threshold))
+        };
+
+        // From the hour under way, so hours that have passed can't start anything
+        let reports: Vec<_> = forecast
+            .hourly_reports()
+            .skip(forecast.current_hour(now))
+            .take(hours + 1)
+            .collect();
+
+        reports
+            .windows(2)
+            .find_map(|pair| {
+                let [before, report] = pair else {
+                    return None;
+                };
+                if holds(before).is_some() {
+                    return None;
+                }
+                Some(Alert {
+                    rule: self.name.clone(),
+                    condition: self.when,
+                    date: report.local_date.clone(),
+                    timeslot: Some(report.timeslot.clone()),
+                    value: holds(report)?,
+                })
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// The alerts already sent, so each is only sent once however often the forecast is refreshed.
+#[derive(Default)]
+pub(crate) struct SentAlerts {
+    /// Rule name, location id, date and timeslot
+    sent: HashSet<(String, String, String, Option<String>)>,
+}
+
+impl SentAlerts {
+    /// Forgets the days that have dropped out of `forecast`, as they can't fire again.
+    pub fn prune(&mut self, forecast: &WeatherForecastResponse) {
+        if let Some(first) = forecast.forecasts.first() {
+            let today = &first.summary.report.local_date;
+            self.sent.retain(|(_, _, date, _)| date >= today);
+        }
+    }
+
+    /// Records `alert` for `location_id`, returning false if it was already sent.
+    pub fn insert(&mut self, alert: &Alert, location_id: &str) -> bool {
+        self.sent.insert(Self::key(alert, location_id))
+    }
+
+    pub fn contains(&self, alert: &Alert, location_id: &str) -> bool {
+        self.sent.contains(&Self::key(alert, location_id))
+    }
+
+    /// Forgets `alert` for `location_id`, so that it is sent again.
+    pub fn remove(&mut self, alert: &Alert, location_id: &str) {
+        self.sent.remove(&Self::key(alert, location_id));
+    }
+
+    fn key(alert: &Alert, location_id: &str) -> (String, String, String, Option<String>) {
+        (
+            alert.rule.clone(),
+            location_id.to_string(),
+            alert.date.clone(),
+            alert.timeslot.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use chrono::TimeZone;
+
+    /// 15:00 at the location, the first hour of the forecast.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 8, 29, 14, 0, 0).unwrap()
+    }
+
+    fn rule(when: &str, day: Day) -> Rule {
+        Rule {
+            name: "test".to_string(),
+            when: Condition::try_from(when.to_string()).unwrap(),
+            day,
+            within: None,
+            webhook: None,
+            body: None,
+            notify: false,
+        }
+    }
+
+    #[test]
+    fn parses_conditions() {
+        let condition = Condition::try_from("rain > 70".to_string()).unwrap();
+        assert_eq!(
+            condition,
+            Condition {
+                metric: Metric::Rain,
+                comparison: Comparison::Above,
+                threshold: 70.0
+            }
+        );
+        assert_eq!(condition.to_string(), "rain > 70");
+
+        let condition = Condition::try_from("min_temp<=-2.5".to_string()).unwrap();
+        assert_eq!(condition.metric, Metric::MinTemp);
+        assert_eq!(condition.comparison, Comparison::AtMost);
+        assert_eq!(condition.threshold, -2.5);
+
+        for invalid in ["rain", "snow > 1", "rain > lots", "> 5", ""] {
+            assert!(
+                Condition::try_from(invalid.to_string()).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn evaluates_the_chosen_days() {
+        let forecast = test_forecast();
+
+        let alerts = rule("max_temp >= 18", Day::Today).evaluate(&forecast, now());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].date, "2024-08-29");
+        assert_eq!(alerts[0].value, 18.0);
+
+        let alerts = rule("gust > 20", Day::Tomorrow).evaluate(&forecast, now());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].date, "2024-08-30");
+        assert_eq!(alerts[0].value, 21.0);
+
+        assert!(rule("min_temp < 0", Day::Any)
+            .evaluate(&forecast, now())
+            .is_empty());
+        assert_eq!(
+            rule("rain >= 0", Day::Any).evaluate(&forecast, now()).len(),
+            14
+        );
+    }
+
+    #[test]
+    fn reports_when_a_condition_starts_within_the_next_hours() {
+        let mut forecast = test_forecast();
+        let reports = &mut forecast.forecasts[0].detailed.reports;
+        reports[2].precipitation_probability_in_percent = 60.0;
+        reports[3].precipitation_probability_in_percent = 80.0;
+
+        let within = |hours| Rule {
+            within: Some(hours),
+            ..rule("rain >= 50", Day::Today)
+        };
+
+        assert!(within(1).evaluate(&forecast, now()).is_empty());
+        let alerts = within(2).evaluate(&forecast, now());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].date, "2024-08-29");
+        assert_eq!(alerts[0].timeslot.as_deref(), Some("17:00"));
+        assert_eq!(alerts[0].value, 60.0);
+
+        // Raining now but dry the hour after, so it starts again at 17:00
+        let reports = &mut forecast.forecasts[0].detailed.reports;
+        reports[0].precipitation_probability_in_percent = 90.0;
+        let alerts = within(2).evaluate(&forecast, now());
+        assert_eq!(alerts[0].timeslot.as_deref(), Some("17:00"));
+
+        // Raining throughout, so it isn't starting
+        forecast.forecasts[0].detailed.reports[1].precipitation_probability_in_percent = 90.0;
+        assert!(within(2).evaluate(&forecast, now()).is_empty());
+    }
+
+    #[test]
+    fn looks_ahead_from_the_hour_under_way() {
+        let mut forecast = test_forecast();
+        let reports = &mut forecast.forecasts[0].detailed.reports;
+        reports[2].precipitation_probability_in_percent = 60.0;
+        let rule = Rule {
+            within: Some(2),
+            ..rule("rain >= 50", Day::Today)
+        };
+
+        // 19:00 at the location, after the rain at 17:00 has been and gone
+        let evening = Utc.with_ymd_and_hms(2024, 8, 29, 18, 0, 0).unwrap();
+        assert!(rule.evaluate(&forecast, evening).is_empty());
+
+        let reports = &mut forecast.forecasts[0].detailed.reports;
+        reports[6].precipitation_probability_in_percent = 70.0;
+        let alerts = rule.evaluate(&forecast, evening);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].timeslot.as_deref(), Some("21:00"));
+    }
+
+    #[test]
+    fn remembers_sent_alerts() {
+        let forecast = test_forecast();
+        let alert = rule("max_temp >= 18", Day::Today)
+            .evaluate(&forecast, now())
+            .remove(0);
+        let mut sent = SentAlerts::default();
+
+        assert!(sent.insert(&alert, "2650584"));
+        assert!(!sent.insert(&alert, "2650584"));
+        assert!(sent.insert(&alert, "2643743"));
+
+        let mut later = forecast.clone();
+        later.forecasts.remove(0);
+        sent.prune(&later);
+        assert!(sent.insert(&alert, "2650584"));
+    }
+}
@@ -0,0 +1,293 @@
+use super::{Alert, Rule, SentAlerts};
+use crate::client::{WeatherAPI, WeatherForecastResponse};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::time::Duration;
+use zbus::zvariant::Value;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// The parts of the freedesktop desktop notifications interface we use.
+#[zbus::proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+}
+
+/// Normal, as opposed to low or critical
+const URGENCY_NORMAL: u8 = 1;
+/// Let the notification server decide
+const DEFAULT_TIMEOUT: i32 = -1;
+
+pub(crate) struct Notifier {
+    proxy: NotificationsProxy<'static>,
+}
+
+impl Notifier {
+    /// Talks to the notification server on the session bus.
+    pub async fn session() -> Result<Self, Error> {
+        let connection = zbus::Connection::session().await?;
+        Ok(Self::new(&connection).await?)
+    }
+
+    pub async fn new(connection: &zbus::Connection) -> zbus::Result<Self> {
+        Ok(Self {
+            proxy: NotificationsProxy::new(connection).await?,
+        })
+    }
+
+    /// Shows a notification for `alert`, returning its id.
+    pub async fn notify(
+        &self,
+        alert: &Alert,
+        forecast: &WeatherForecastResponse,
+    ) -> zbus::Result<u32> {
+        let (summary, body) = notification_text(alert, forecast);
+        let hints = HashMap::from([("urgency", Value::U8(URGENCY_NORMAL))]);
+        self.proxy
+            .notify(
+                "weathema",
+                0,
+                "weather-severe-alert",
+                &summary,
+                &body,
+                &[],
+                hints,
+                DEFAULT_TIMEOUT,
+            )
+            .await
+    }
+}
+
+/// The summary and body of the notification for `alert`.
+fn notification_text(alert: &Alert, forecast: &WeatherForecastResponse) -> (String, String) {
+    let metric = alert.condition.metric;
+    let when = match &alert.timeslot {
+        Some(timeslot) => format!("from {} on {}", timeslot, alert.date),
+        None => format!("on {}", alert.date),
+    };
+    (
+        format!("{} in {}", alert.rule, forecast.location.name),
+        format!(
+            "{} {}{} {}",
+            metric.name(),
+            alert.value,
+            metric.unit(),
+            when
+        ),
+    )
+}
+
+/// Checks the forecast for `locations` every `interval` and sends a desktop notification for
+/// each new alert from the rules with `notify` set, until interrupted.
+pub(crate) async fn watch(
+    weather_api: WeatherAPI,
+    locations: Vec<String>,
+    rules: Vec<Rule>,
+    interval: Duration,
+) -> Result<(), Error> {
+    let rules: Vec<_> = rules.into_iter().filter(|rule| rule.notify).collect();
+    if rules.is_empty() {
+        return Err("No rules with `notify = true` in the config file".into());
+    }
+    if locations.is_empty() {
+        return Err(
+            "No locations to watch, pass --location or list them in the config file".into(),
+        );
+    }
+
+    let notifier = Notifier::session().await?;
+    let mut sent = SentAlerts::default();
+    let mut ticks = tokio::time::interval(interval);
+
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+
+        let forecasts = futures::future::join_all(
+            locations
+                .iter()
+                .map(|location| weather_api.get_weather(location)),
+        )
+        .await;
+        for (location, forecast) in locations.iter().zip(forecasts) {
+            let forecast = match forecast {
+                Ok(forecast) => forecast,
+                Err(err) => {
+                    eprintln!("Couldn't refresh {}: {}", location, err);
+                    continue;
+                }
+            };
+
+            sent.prune(&forecast);
+            for alert in rules
+                .iter()
+                .flat_map(|rule| rule.evaluate(&forecast, Utc::now()))
+            {
+                if sent.contains(&alert, &forecast.location.id) {
+                    continue;
+                }
+                // Only counted as sent once shown, so a failure is tried again next time
+                match notifier.notify(&alert, &forecast).await {
+                    Ok(_) => {
+                        sent.insert(&alert, &forecast.location.id);
+                    }
+                    Err(err) => {
+                        eprintln!("Couldn't send a notification for {}: {}", alert.rule, err)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use crate::rules::{Condition, Day};
+    use chrono::{DateTime, TimeZone};
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use zbus::zvariant::OwnedValue;
+
+    /// 15:00 at the location, the first hour of the forecast.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 8, 29, 14, 0, 0).unwrap()
+    }
+
+    fn rain_soon() -> Rule {
+        Rule {
+            name: "Rain soon".to_string(),
+            when: Condition::try_from("rain >= 50".to_string()).unwrap(),
+            day: Day::Today,
+            within: Some(2),
+            webhook: None,
+            body: None,
+            notify: true,
+        }
+    }
+
+    fn rainy_forecast() -> WeatherForecastResponse {
+        let mut forecast = test_forecast();
+        forecast.forecasts[0].detailed.reports[2].precipitation_probability_in_percent = 60.0;
+        forecast
+    }
+
+    #[test]
+    fn describes_alerts() {
+        let forecast = rainy_forecast();
+        let alert = rain_soon().evaluate(&forecast, now()).remove(0);
+        assert_eq!(
+            notification_text(&alert, &forecast),
+            (
+                "Rain soon in Dyserth".to_string(),
+                "rain 60% from 17:00 on 2024-08-29".to_string()
+            )
+        );
+    }
+
+    type Received = Arc<Mutex<Vec<(String, String, String, u8)>>>;
+
+    /// Records notifications instead of showing them.
+    struct MockNotificationServer {
+        received: Received,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl MockNotificationServer {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            app_name: String,
+            _replaces_id: u32,
+            _app_icon: String,
+            summary: String,
+            body: String,
+            _actions: Vec<String>,
+            hints: HashMap<String, OwnedValue>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            let urgency = hints
+                .get("urgency")
+                .and_then(|urgency| u8::try_from(urgency).ok())
+                .unwrap_or_default();
+            let mut received = self.received.lock().unwrap();
+            received.push((app_name, summary, body, urgency));
+            received.len() as u32
+        }
+    }
+
+    /// Needs `dbus-daemon` on the `PATH` to run a private session bus, run with
+    /// `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore]
+    async fn notifies_a_session_bus() {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("dbus-daemon should be installed");
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take().unwrap())
+            .read_line(&mut address)
+            .unwrap();
+        let address = address.trim();
+
+        let received = Received::default();
+        let _server = zbus::connection::Builder::address(address)
+            .unwrap()
+            .name("org.freedesktop.Notifications")
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/Notifications",
+                MockNotificationServer {
+                    received: received.clone(),
+                },
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let connection = zbus::connection::Builder::address(address)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let notifier = Notifier::new(&connection).await.unwrap();
+
+        let forecast = rainy_forecast();
+        let alert = rain_soon().evaluate(&forecast, now()).remove(0);
+        assert_eq!(notifier.notify(&alert, &forecast).await.unwrap(), 1);
+
+        daemon.kill().unwrap();
+        daemon.wait().unwrap();
+        assert_eq!(
+            *received.lock().unwrap(),
+            [(
+                "weathema".to_string(),
+                "Rain soon in Dyserth".to_string(),
+                "rain 60% from 17:00 on 2024-08-29".to_string(),
+                URGENCY_NORMAL
+            )]
+        );
+    }
+}
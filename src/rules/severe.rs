@@ -0,0 +1,270 @@
+use crate::client::{Units, WeatherForecastResponse, WeatherSummaryReport};
+
+/// Ordered from least to most severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Warning,
+    Severe,
+}
+
+impl Severity {
+    /// The banner colour.
+    pub fn colour(self) -> &'static str {
+        match self {
+            Severity::Warning => "yellow",
+            Severity::Severe => "red",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Hazard {
+    Frost,
+    Heat,
+    HighWinds,
+    HeavyRain,
+    HighUv,
+    HighPollen,
+}
+
+/// A hazard forecast for today or tomorrow.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SevereAlert {
+    pub hazard: Hazard,
+    pub severity: Severity,
+    /// The forecast's location id
+    pub location_id: String,
+    /// 0 for today, 1 for tomorrow
+    pub day: usize,
+    pub date: String,
+    /// The reading that set it off, in °C, kph, % or index points
+    pub value: f64,
+}
+
+/// Met Office weather types for heavy rain showers, heavy rain and thunder.
+fn is_heavy_rain(weather_type: u8) -> bool {
+    matches!(weather_type, 13..=15 | 28..=30)
+}
+
+/// The hazards for one day, each with its severity and reading.
+fn hazards(report: &WeatherSummaryReport) -> Vec<(Hazard, Severity, f64)> {
+    let mut hazards = Vec::new();
+    let mut check =
+        |hazard, value: Option<f64>, warning: fn(f64) -> bool, severe: fn(f64) -> bool| {
+            if let Some(value) = value {
+                if severe(value) {
+                    hazards.push((hazard, Severity::Severe, value));
+                } else if warning(value) {
+                    hazards.push((hazard, Severity::Warning, value));
+                }
+            }
+        };
+
+    check(
+        Hazard::Frost,
+        Some(report.min_temp_c),
+        |t| t < 0.0,
+        |t| t <= -5.0,
+    );
+    check(
+        Hazard::Heat,
+        Some(report.max_temp_c),
+        |t| t >= 27.0,
+        |t| t >= 32.0,
+    );
+    check(
+        Hazard::HighWinds,
+        report.gust_speed_kph,
+        |g| g >= 65.0,
+        |g| g >= 90.0,
+    );
+    check(
+        Hazard::HeavyRain,
+        is_heavy_rain(report.weather_type).then_some(report.precipitation_probability_in_percent),
+        |p| p >= 50.0,
+        |p| p >= 80.0,
+    );
+    check(
+        Hazard::HighUv,
+        report.uv_index,
+        |uv| uv >= 6.0,
+        |uv| uv >= 8.0,
+    );
+    check(
+        Hazard::HighPollen,
+        report.pollen_index,
+        |p| p >= 7.0,
+        |p| p >= 10.0,
+    );
+    hazards
+}
+
+/// The hazards forecast for today and tomorrow.
+pub(crate) fn assess(forecast: &WeatherForecastResponse) -> Vec<SevereAlert> {
+    let location_id = &forecast.location.id;
+    forecast
+        .forecasts
+        .iter()
+        .take(2)
+        .enumerate()
+        .flat_map(|(day, forecast)| {
+            let report = &forecast.summary.report;
+            hazards(report)
+                .into_iter()
+                .map(move |(hazard, severity, value)| SevereAlert {
+                    hazard,
+                    severity,
+                    location_id: location_id.clone(),
+                    day,
+                    date: report.local_date.clone(),
+                    value,
+                })
+        })
+        .collect()
+}
+
+impl SevereAlert {
+    /// Identifies the alert across refreshes, so a dismissed alert stays dismissed for its location
+    /// but the same hazard elsewhere is still shown.
+    pub fn key(&self) -> (String, String, String) {
+        (
+            self.location_id.clone(),
+            self.date.clone(),
+            format!("{:?}", self.hazard),
+        )
+    }
+
+    pub fn describe(&self, units: Units) -> String {
+        let day = if self.day == 0 { "Today" } else { "Tomorrow" };
+        let temperature = |celsius| {
+            format!(
+                "{:.0}°{}",
+                units.temperature(celsius),
+                units.temperature_symbol()
+            )
+        };
+        let description = match (self.hazard, self.severity) {
+            (Hazard::Frost, Severity::Warning) => format!("frost, low {}", temperature(self.value)),
+            (Hazard::Frost, Severity::Severe) => {
+                format!("hard frost, low {}", temperature(self.value))
+            }
+            (Hazard::Heat, Severity::Warning) => format!("heat, high {}", temperature(self.value)),
+            (Hazard::Heat, Severity::Severe) => {
+                format!("extreme heat, high {}", temperature(self.value))
+            }
+            (Hazard::HighWinds, _) => format!(
+                "high winds, gusts {:.0} {}",
+                units.speed(self.value),
+                units.speed_symbol()
+            ),
+            (Hazard::HeavyRain, _) => format!("heavy rain, {:.0}% chance", self.value),
+            (Hazard::HighUv, Severity::Warning) => format!("high UV {:.0}", self.value),
+            (Hazard::HighUv, Severity::Severe) => format!("very high UV {:.0}", self.value),
+            (Hazard::HighPollen, Severity::Warning) => format!("high pollen {:.0}", self.value),
+            (Hazard::HighPollen, Severity::Severe) => {
+                format!("very high pollen {:.0}", self.value)
+            }
+        };
+        format!("{}: {}", day, description)
+    }
+}
+
+/// The banner text and colour for a set of alerts, empty when there are none.
+pub(crate) fn banner(alerts: &[SevereAlert], units: Units) -> (String, &'static str) {
+    let text = alerts
+        .iter()
+        .map(|alert| alert.describe(units))
+        .collect::<Vec<_>>()
+        .join("  ·  ");
+    let colour = alerts
+        .iter()
+        .map(|alert| alert.severity)
+        .max()
+        .unwrap_or(Severity::Warning)
+        .colour();
+    (text, colour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+
+    #[test]
+    fn finds_nothing_on_a_mild_day() {
+        assert!(assess(&test_forecast()).is_empty());
+    }
+
+    #[test]
+    fn grades_hazards_for_today_and_tomorrow() {
+        let mut forecast = test_forecast();
+        let today = &mut forecast.forecasts[0].summary.report;
+        today.min_temp_c = -6.0;
+        today.uv_index = Some(6.0);
+        let tomorrow = &mut forecast.forecasts[1].summary.report;
+        tomorrow.gust_speed_kph = Some(70.0);
+        tomorrow.weather_type = 15;
+        tomorrow.precipitation_probability_in_percent = 85.0;
+        // Later days are left to the graph
+        forecast.forecasts[2].summary.report.max_temp_c = 35.0;
+
+        let alerts = assess(&forecast);
+        let summary: Vec<_> = alerts
+            .iter()
+            .map(|alert| (alert.day, alert.hazard, alert.severity))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0, Hazard::Frost, Severity::Severe),
+                (0, Hazard::HighUv, Severity::Warning),
+                (1, Hazard::HighWinds, Severity::Warning),
+                (1, Hazard::HeavyRain, Severity::Severe),
+            ]
+        );
+
+        let (text, colour) = banner(&alerts, Units::Metric);
+        assert_eq!(
+            text,
+            "Today: hard frost, low -6°C  ·  Today: high UV 6  ·  \
+             Tomorrow: high winds, gusts 70 kph  ·  Tomorrow: heavy rain, 85% chance"
+        );
+        assert_eq!(colour, "red");
+        assert!(alerts.iter().all(|alert| alert.location_id == "2650584"));
+    }
+
+    #[test]
+    fn keys_alerts_by_location() {
+        let mut forecast = test_forecast();
+        forecast.forecasts[0].summary.report.min_temp_c = -1.0;
+        let here = assess(&forecast).remove(0);
+        forecast.location.id = "2643743".to_string();
+        let elsewhere = assess(&forecast).remove(0);
+
+        assert_eq!(here.key().1, elsewhere.key().1);
+        assert_ne!(here.key(), elsewhere.key());
+    }
+
+    #[test]
+    fn describes_in_the_chosen_units() {
+        let alert = SevereAlert {
+            hazard: Hazard::Frost,
+            severity: Severity::Warning,
+            location_id: "2650584".to_string(),
+            day: 1,
+            date: "2024-08-30".to_string(),
+            value: -1.0,
+        };
+        assert_eq!(alert.describe(Units::Imperial), "Tomorrow: frost, low 30°F");
+        assert_eq!(banner(&[alert], Units::Metric).1, "yellow");
+    }
+
+    #[test]
+    fn ignores_light_rain() {
+        let mut forecast = test_forecast();
+        let today = &mut forecast.forecasts[0].summary.report;
+        today.weather_type = 12;
+        today.precipitation_probability_in_percent = 95.0;
+        assert!(assess(&forecast).is_empty());
+    }
+}
@@ -0,0 +1,294 @@
+use super::{Alert, Rule, SentAlerts};
+use crate::client::WeatherForecastResponse;
+use crate::output::template;
+use chrono::Utc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long to wait for a webhook to answer before giving up on it.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sent when a rule doesn't set its own `body`.
+pub(crate) const DEFAULT_BODY: &str = r#"{"rule": "{rule}", "condition": "{condition}", "location": "{location}", "location_id": "{location_id}", "date": "{date}", "value": {value}}"#;
+
+/// Escapes a value for use inside a JSON string.
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::Value::from(value).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Looks up the value of a `{placeholder}` in a body template.
+fn placeholder(name: &str, alert: &Alert, forecast: &WeatherForecastResponse) -> Option<String> {
+    let value = match name {
+        "rule" => json_escape(&alert.rule),
+        "condition" => json_escape(&alert.condition.to_string()),
+        "metric" => alert.condition.metric.name().to_string(),
+        "threshold" => alert.condition.threshold.to_string(),
+        "value" => alert.value.to_string(),
+        "date" => alert.date.clone(),
+        "time" => alert.timeslot.clone().unwrap_or_default(),
+        "location" => json_escape(&forecast.location.name),
+        "location_id" => json_escape(&forecast.location.id),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Fills in the `{placeholders}` of a JSON body template.
+///
+/// String values are escaped but not quoted, so the template decides whether a value is a JSON
+/// string or number, e.g. `{"text": "{rule} on {date}", "value": {value}}`. Braces that aren't
+/// a known placeholder are copied through, so the template's own objects are left alone.
+pub(crate) fn render_body(
+    body_template: &str,
+    alert: &Alert,
+    forecast: &WeatherForecastResponse,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let output = template::fill(body_template, |name| placeholder(name, alert, forecast));
+
+    serde_json::from_str::<serde_json::Value>(&output).map_err(|err| {
+        format!(
+            "The body for rule {:?} isn't valid JSON: {}",
+            alert.rule, err
+        )
+    })?;
+    Ok(output)
+}
+
+/// A webhook POST waiting to be sent.
+pub(crate) struct Delivery {
+    client: reqwest::Client,
+    pub rule: String,
+    url: String,
+    body: String,
+    alert: Alert,
+    location_id: String,
+    sent: Arc<Mutex<SentAlerts>>,
+}
+
+impl Delivery {
+    /// Posts the webhook, forgetting the alert if that fails so the next refresh tries it again.
+    pub async fn send(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let result = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .timeout(TIMEOUT)
+            .body(self.body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+
+        if result.is_err() {
+            self.sent
+                .lock()
+                .unwrap()
+                .remove(&self.alert, &self.location_id);
+        }
+        result?;
+        Ok(())
+    }
+}
+
+/// The rules with a webhook, and the alerts already sent for them.
+pub(crate) struct Webhooks {
+    client: reqwest::Client,
+    rules: Vec<Rule>,
+    /// Shared with the deliveries, which take back their alert if the post fails
+    sent: Arc<Mutex<SentAlerts>>,
+}
+
+impl Webhooks {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            rules: rules
+                .into_iter()
+                .filter(|rule| rule.webhook.is_some())
+                .collect(),
+            sent: Arc::default(),
+        }
+    }
+
+    /// Evaluates every rule against a fresh forecast, returning the webhooks to send.
+    ///
+    /// Each alert is only sent once per rule, location and day or hour, however often the forecast
+    /// is refreshed. It counts as sent while its delivery is under way, so a refresh in the
+    /// meantime doesn't post it twice, and a failed post is tried again on the next refresh. Body
+    /// templates that don't render are reported as errors and not retried.
+    pub fn due(
+        &mut self,
+        forecast: &WeatherForecastResponse,
+    ) -> Vec<Result<Delivery, Box<dyn std::error::Error + Send + Sync>>> {
+        let mut sent = self.sent.lock().unwrap();
+        sent.prune(forecast);
+
+        let mut deliveries = Vec::new();
+        for rule in &self.rules {
+            let Some(url) = &rule.webhook else {
+                continue;
+            };
+            for alert in rule.evaluate(forecast, Utc::now()) {
+                if !sent.insert(&alert, &forecast.location.id) {
+                    continue;
+                }
+
+                let body_template = rule.body.as_deref().unwrap_or(DEFAULT_BODY);
+                deliveries.push(render_body(body_template, &alert, forecast).map(|body| {
+                    Delivery {
+                        client: self.client.clone(),
+                        rule: rule.name.clone(),
+                        url: url.clone(),
+                        body,
+                        alert,
+                        location_id: forecast.location.id.clone(),
+                        sent: self.sent.clone(),
+                    }
+                }));
+            }
+        }
+        deliveries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use crate::rules::{Condition, Day};
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use chrono::{DateTime, TimeZone};
+
+    /// 15:00 at the location, the first hour of the forecast.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 8, 29, 14, 0, 0).unwrap()
+    }
+
+    fn rule(name: &str, when: &str, day: Day, url: &str, body: Option<&str>) -> Rule {
+        Rule {
+            name: name.to_string(),
+            when: Condition::try_from(when.to_string()).unwrap(),
+            day,
+            within: None,
+            webhook: Some(url.to_string()),
+            body: body.map(str::to_string),
+            notify: false,
+        }
+    }
+
+    #[test]
+    fn renders_the_default_body() {
+        let forecast = test_forecast();
+        let rule = rule("Gusty \"tomorrow\"", "gust > 20", Day::Tomorrow, "", None);
+        let alert = rule.evaluate(&forecast, now()).remove(0);
+
+        let body: serde_json::Value =
+            serde_json::from_str(&render_body(DEFAULT_BODY, &alert, &forecast).unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "rule": "Gusty \"tomorrow\"",
+                "condition": "gust > 20",
+                "location": "Dyserth",
+                "location_id": "2650584",
+                "date": "2024-08-30",
+                "value": 21,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_bodies_that_are_not_json() {
+        let forecast = test_forecast();
+        let rule = rule("Warm", "max_temp > 10", Day::Today, "", None);
+        let alert = rule.evaluate(&forecast, now()).remove(0);
+
+        assert!(render_body("{\"value\": {value}", &alert, &forecast).is_err());
+        assert_eq!(
+            render_body("{\"text\": \"{rule} {unknown}\"}", &alert, &forecast).unwrap(),
+            "{\"text\": \"Warm {unknown}\"}"
+        );
+    }
+
+    #[tokio::test]
+    async fn posts_each_alert_once() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new().route(
+            "/hook",
+            post({
+                let received = received.clone();
+                move |Json(body): Json<serde_json::Value>| async move {
+                    received.lock().unwrap().push(body);
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut webhooks = Webhooks::new(vec![
+            rule(
+                "Rain",
+                "rain > 60",
+                Day::Any,
+                &url,
+                Some(r#"{"text": "{rule}: {value}% on {date} in {location}"}"#),
+            ),
+            rule("Frost", "min_temp < 0", Day::Any, &url, None),
+        ]);
+        let forecast = test_forecast();
+
+        let deliveries = webhooks.due(&forecast);
+        assert_eq!(deliveries.len(), 1);
+        for delivery in deliveries {
+            delivery.unwrap().send().await.unwrap();
+        }
+        // Refreshing doesn't send the same alert again
+        assert!(webhooks.due(&forecast).is_empty());
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            [serde_json::json!({"text": "Rain: 65% on 2024-09-03 in Dyserth"})]
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_alerts_that_fail_to_post() {
+        let attempts = Arc::new(Mutex::new(0));
+        let app = Router::new().route(
+            "/hook",
+            post({
+                let attempts = attempts.clone();
+                move || async move {
+                    let mut attempts = attempts.lock().unwrap();
+                    *attempts += 1;
+                    // Down the first time
+                    match *attempts {
+                        1 => StatusCode::SERVICE_UNAVAILABLE,
+                        _ => StatusCode::OK,
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut webhooks = Webhooks::new(vec![rule("Rain", "rain > 60", Day::Any, &url, None)]);
+        let forecast = test_forecast();
+
+        let mut deliveries = webhooks.due(&forecast);
+        let delivery = deliveries.remove(0).unwrap();
+        // Not sent again while the first post is still under way
+        assert!(webhooks.due(&forecast).is_empty());
+        assert!(delivery.send().await.is_err());
+
+        let mut deliveries = webhooks.due(&forecast);
+        assert_eq!(deliveries.len(), 1);
+        deliveries.remove(0).unwrap().send().await.unwrap();
+        assert!(webhooks.due(&forecast).is_empty());
+        assert_eq!(*attempts.lock().unwrap(), 2);
+    }
+}
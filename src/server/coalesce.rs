@@ -0,0 +1,79 @@
+use crate::client::{is_not_found, normalise_location, WeatherAPI, WeatherForecastResponse};
+use axum::http::StatusCode;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub(crate) type FetchResult = Result<Arc<WeatherForecastResponse>, (StatusCode, String)>;
+
+/// Shares one upstream request between every caller asking for the same location at once.
+pub(crate) struct ForecastService {
+    weather_api: Arc<WeatherAPI>,
+    in_flight: Mutex<HashMap<String, Shared<BoxFuture<'static, FetchResult>>>>,
+}
+
+impl ForecastService {
+    pub fn new(weather_api: WeatherAPI) -> Self {
+        Self {
+            weather_api: Arc::new(weather_api),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn weather_api(&self) -> &WeatherAPI {
+        &self.weather_api
+    }
+
+    pub async fn forecast(&self, location: &str) -> FetchResult {
+        let key = normalise_location(location)
+            .map(|location| location.to_lowercase())
+            .map_err(|err| (error_status(err.as_ref()), err.to_string()))?;
+
+        let fetch = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            in_flight
+                .entry(key.clone())
+                .or_insert_with(|| {
+                    let weather_api = self.weather_api.clone();
+                    let location = location.to_string();
+                    async move {
+                        weather_api
+                            .get_weather(&location)
+                            .await
+                            .map(Arc::new)
+                            .map_err(|err| (error_status(err.as_ref()), err.to_string()))
+                    }
+                    .boxed()
+                    .shared()
+                })
+                .clone()
+        };
+
+        let result = fetch.clone().await;
+
+        // Later callers should make a new request rather than reuse this result
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| current.ptr_eq(&fetch))
+        {
+            in_flight.remove(&key);
+        }
+        result
+    }
+}
+
+/// Maps a lookup error onto the status code to answer with.
+///
+/// Unknown locations are 404 whether the locator found nothing or the forecast service didn't know
+/// the id, any other upstream failure is a 502.
+pub(crate) fn error_status(err: &(dyn std::error::Error + Send + Sync + 'static)) -> StatusCode {
+    if is_not_found(err) {
+        return StatusCode::NOT_FOUND;
+    }
+    match err.downcast_ref::<std::io::Error>().map(|err| err.kind()) {
+        Some(std::io::ErrorKind::InvalidInput) => StatusCode::BAD_REQUEST,
+        Some(std::io::ErrorKind::NotFound) => StatusCode::NOT_FOUND,
+        _ => StatusCode::BAD_GATEWAY,
+    }
+}
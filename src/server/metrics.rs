@@ -0,0 +1,247 @@
+use super::coalesce::ForecastService;
+use crate::client::{WeatherForecastResponse, WindDirection, LATENCY_BUCKETS};
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+
+/// Escapes a label value for the Prometheus text exposition format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn header(output: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(output, "# HELP {} {}", name, help);
+    let _ = writeln!(output, "# TYPE {} {}", name, kind);
+}
+
+type Gauge = fn(&WeatherForecastResponse, DateTime<Utc>) -> Option<f64>;
+
+/// The per-location gauges, taken from today's summary or the report for the hour under way.
+const GAUGES: [(&str, &str, Gauge); 9] = [
+    (
+        "weathema_temperature_celsius",
+        "Current temperature",
+        |forecast, now| Some(forecast.current_report(now)?.temperature_c),
+    ),
+    (
+        "weathema_temperature_min_celsius",
+        "Minimum temperature today",
+        |forecast, _| Some(forecast.forecasts.first()?.summary.report.min_temp_c),
+    ),
+    (
+        "weathema_temperature_max_celsius",
+        "Maximum temperature today",
+        |forecast, _| Some(forecast.forecasts.first()?.summary.report.max_temp_c),
+    ),
+    (
+        "weathema_wind_speed_kph",
+        "Current wind speed",
+        |forecast, now| Some(forecast.current_report(now)?.wind_speed_kph),
+    ),
+    (
+        "weathema_wind_direction_degrees",
+        "Current direction the wind blows from, clockwise from north",
+        |forecast, now| {
+            let report = forecast.current_report(now)?;
+            Some(WindDirection::parse(&report.wind_direction)?.degrees())
+        },
+    ),
+    (
+        "weathema_precipitation_probability_percent",
+        "Current chance of precipitation",
+        |forecast, now| {
+            let report = forecast.current_report(now)?;
+            Some(report.precipitation_probability_in_percent)
+        },
+    ),
+    (
+        "weathema_humidity_percent",
+        "Current relative humidity",
+        |forecast, now| forecast.current_report(now)?.humidity,
+    ),
+    (
+        "weathema_pressure_hpa",
+        "Current mean sea level pressure",
+        |forecast, now| forecast.current_report(now)?.pressure,
+    ),
+    ("weathema_uv_index", "UV index today", |forecast, _| {
+        forecast.forecasts.first()?.summary.report.uv_index
+    }),
+];
+
+/// Renders the Prometheus metrics for `locations` along with the upstream request metrics.
+pub(crate) async fn render(service: &ForecastService, locations: &[String]) -> String {
+    let forecasts =
+        futures::future::join_all(locations.iter().map(|location| service.forecast(location)))
+            .await;
+
+    let now = Utc::now();
+    let mut output = String::new();
+
+    header(
+        &mut output,
+        "weathema_location_up",
+        "gauge",
+        "Whether the last forecast lookup for the location succeeded",
+    );
+    for (location, forecast) in locations.iter().zip(&forecasts) {
+        let _ = writeln!(
+            output,
+            "weathema_location_up{{location=\"{}\"}} {}",
+            escape(location),
+            if forecast.is_ok() { 1 } else { 0 }
+        );
+    }
+
+    for (name, help, gauge) in GAUGES {
+        header(&mut output, name, "gauge", help);
+        for (location, forecast) in locations.iter().zip(&forecasts) {
+            let Ok(forecast) = forecast else {
+                continue;
+            };
+            if let Some(value) = gauge(forecast, now) {
+                let _ = writeln!(
+                    output,
+                    "{}{{location=\"{}\",location_id=\"{}\",name=\"{}\"}} {}",
+                    name,
+                    escape(location),
+                    escape(&forecast.location.id),
+                    escape(&forecast.location.name),
+                    value
+                );
+            }
+        }
+    }
+
+    let snapshot = service.weather_api().metrics().snapshot();
+
+    header(
+        &mut output,
+        "weathema_upstream_requests_total",
+        "counter",
+        "Requests made to the upstream weather services",
+    );
+    for endpoint in &snapshot {
+        let _ = writeln!(
+            output,
+            "weathema_upstream_requests_total{{endpoint=\"{}\"}} {}",
+            endpoint.endpoint.name(),
+            endpoint.requests
+        );
+    }
+
+    header(
+        &mut output,
+        "weathema_upstream_errors_total",
+        "counter",
+        "Requests to the upstream weather services that failed",
+    );
+    for endpoint in &snapshot {
+        let _ = writeln!(
+            output,
+            "weathema_upstream_errors_total{{endpoint=\"{}\"}} {}",
+            endpoint.endpoint.name(),
+            endpoint.errors
+        );
+    }
+
+    header(
+        &mut output,
+        "weathema_upstream_request_duration_seconds",
+        "histogram",
+        "Time taken by requests to the upstream weather services",
+    );
+    for endpoint in &snapshot {
+        let name = endpoint.endpoint.name();
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(endpoint.buckets) {
+            let _ = writeln!(
+                output,
+                "weathema_upstream_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"{}\"}} {}",
+                name, bound, count
+            );
+        }
+        let _ = writeln!(
+            output,
+            "weathema_upstream_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"+Inf\"}} {}",
+            name, endpoint.requests
+        );
+        let _ = writeln!(
+            output,
+            "weathema_upstream_request_duration_seconds_sum{{endpoint=\"{}\"}} {}",
+            name, endpoint.duration_seconds
+        );
+        let _ = writeln!(
+            output,
+            "weathema_upstream_request_duration_seconds_count{{endpoint=\"{}\"}} {}",
+            name, endpoint.requests
+        );
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_forecast;
+    use chrono::TimeZone;
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn reads_gauges_from_forecast() {
+        let forecast = test_forecast();
+        // 15:00 at the location, the first hour of the forecast
+        let now = Utc.with_ymd_and_hms(2024, 8, 29, 14, 0, 0).unwrap();
+
+        let values: Vec<_> = GAUGES
+            .iter()
+            .map(|(_, _, gauge)| gauge(&forecast, now))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                Some(18.0),
+                Some(9.0),
+                Some(18.0),
+                Some(17.0),
+                Some(270.0),
+                Some(0.0),
+                Some(61.0),
+                Some(1017.0),
+                Some(5.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_the_current_hour_of_an_older_forecast() {
+        let forecast = test_forecast();
+        // 21:00 at the location, six hours after the forecast starts
+        let now = Utc.with_ymd_and_hms(2024, 8, 29, 20, 0, 0).unwrap();
+
+        let values: Vec<_> = GAUGES
+            .iter()
+            .map(|(_, _, gauge)| gauge(&forecast, now))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                Some(14.0),
+                Some(9.0),
+                Some(18.0),
+                Some(10.0),
+                Some(225.0),
+                Some(0.0),
+                Some(77.0),
+                Some(1021.0),
+                Some(5.0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+mod coalesce;
+mod metrics;
+
+use crate::client::WeatherAPI;
+use crate::output::json::{DailyDocument, HourlyDocument, LocationsDocument};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use coalesce::{error_status, ForecastService};
+use serde::Deserialize;
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+const MAX_DAYS: usize = 14;
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+}
+
+#[derive(Deserialize)]
+struct ForecastParams {
+    location: String,
+    days: Option<usize>,
+}
+
+impl ForecastParams {
+    fn days(&self) -> usize {
+        self.days.unwrap_or(MAX_DAYS).clamp(1, MAX_DAYS)
+    }
+}
+
+struct AppState {
+    service: ForecastService,
+    /// The locations reported on at `/metrics`
+    metrics_locations: Vec<String>,
+}
+
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/metrics", get(prometheus_metrics))
+        .route("/api/v1/locations", get(locations))
+        .route("/api/v1/forecast/daily", get(daily))
+        .route("/api/v1/forecast/hourly", get(hourly))
+        .with_state(state)
+}
+
+/// Serves the JSON API and metrics until interrupted with Ctrl-C.
+pub(crate) async fn serve(
+    bind: SocketAddr,
+    weather_api: WeatherAPI,
+    metrics_locations: Vec<String>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+
+    let state = Arc::new(AppState {
+        service: ForecastService::new(weather_api),
+        metrics_locations,
+    });
+    axum::serve(listener, router(state))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+async fn health() -> &'static str {
+    "ok"
+}
+
+async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> Response {
+    let body = metrics::render(&state.service, &state.metrics_locations).await;
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        body,
+    )
+        .into_response()
+}
+
+async fn locations(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchParams>,
+) -> Response {
+    match state
+        .service
+        .weather_api()
+        .search_locations(&params.q)
+        .await
+    {
+        Ok(results) => Json(LocationsDocument::new(&results)).into_response(),
+        Err(err) => error_response(error_status(err.as_ref()), err.to_string()),
+    }
+}
+
+async fn daily(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ForecastParams>,
+) -> Response {
+    match state.service.forecast(&params.location).await {
+        Ok(forecast) => Json(DailyDocument::new(&forecast, params.days())).into_response(),
+        Err((status, message)) => error_response(status, message),
+    }
+}
+
+async fn hourly(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ForecastParams>,
+) -> Response {
+    match state.service.forecast(&params.location).await {
+        Ok(forecast) => Json(HourlyDocument::new(&forecast, params.days())).into_response(),
+        Err((status, message)) => error_response(status, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::upstream::{self, Upstream};
+    use crate::client::{test_forecast, WindDirection};
+    use crate::output::json::SCHEMA_VERSION;
+    use std::sync::atomic::Ordering;
+
+    /// Starts the stand-in upstream and a server using it, returning the server's base URL.
+    async fn start() -> (String, Arc<Upstream>) {
+        let (weather_api, upstream) = upstream::start().await;
+        let state = AppState {
+            service: ForecastService::new(weather_api),
+            metrics_locations: vec!["Dyserth".to_string(), "Nowhere".to_string()],
+        };
+        let address = upstream::spawn(router(Arc::new(state))).await;
+        (format!("http://{}", address), upstream)
+    }
+
+    async fn get_json(url: String) -> (StatusCode, serde_json::Value) {
+        let response = reqwest::get(url).await.unwrap();
+        let status = StatusCode::from_u16(response.status().as_u16()).unwrap();
+        (status, response.json().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn serves_daily_forecasts() {
+        let (base, _) = start().await;
+        let (status, body) = get_json(format!(
+            "{}/api/v1/forecast/daily?location=Dyserth&days=2",
+            base
+        ))
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["schema_version"], SCHEMA_VERSION);
+        assert_eq!(body["location"]["name"], "Dyserth");
+        assert_eq!(body["days"].as_array().unwrap().len(), 2);
+        assert_eq!(body["days"][0]["local_date"], "2024-08-29");
+        assert_eq!(body["days"][0]["max_temp_c"], 18.0);
+    }
+
+    #[tokio::test]
+    async fn serves_hourly_forecasts() {
+        let (base, _) = start().await;
+        let (status, body) = get_json(format!(
+            "{}/api/v1/forecast/hourly?location=2650584&days=1",
+            base
+        ))
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["hours"].as_array().unwrap().len(), 15);
+        assert_eq!(body["hours"][0]["timeslot"], "15:00");
+    }
+
+    #[tokio::test]
+    async fn searches_locations() {
+        let (base, _) = start().await;
+        let (status, body) = get_json(format!("{}/api/v1/locations?q=Dyserth", base)).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"][0]["id"], "2650584");
+        assert_eq!(body["results"][0]["country"], "GB");
+    }
+
+    #[tokio::test]
+    async fn reports_errors() {
+        let (base, _) = start().await;
+
+        let (status, body) =
+            get_json(format!("{}/api/v1/forecast/daily?location=Nowhere", base)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "No results found");
+
+        let (status, _) = get_json(format!("{}/api/v1/forecast/daily?location=%20", base)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = get_json(format!("{}/api/v1/forecast/daily?location=1", base)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // The forecast service itself failing
+        let (status, _) = get_json(format!("{}/api/v1/forecast/daily?location=500", base)).await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn coalesces_concurrent_lookups() {
+        let (base, upstream) = start().await;
+
+        let requests = (0..10).map(|i| {
+            let location = if i % 2 == 0 { "Dyserth" } else { "%20dyserth" };
+            get_json(format!(
+                "{}/api/v1/forecast/daily?location={}",
+                base, location
+            ))
+        });
+        for (status, _) in futures::future::join_all(requests).await {
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        assert_eq!(upstream.locator_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(upstream.forecast_requests.load(Ordering::SeqCst), 1);
+
+        // Once answered, the next lookup goes upstream again
+        get_json(format!("{}/api/v1/forecast/daily?location=Dyserth", base)).await;
+        assert_eq!(upstream.forecast_requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn exports_prometheus_metrics() {
+        let (base, _) = start().await;
+        let response = reqwest::get(format!("{}/metrics", base)).await.unwrap();
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/plain; version=0.0.4; charset=utf-8"
+        );
+        let body = response.text().await.unwrap();
+
+        // The gauges read the hour under way, long after the test forecast has run out
+        let forecast = test_forecast();
+        let current = forecast.current_report(chrono::Utc::now()).unwrap();
+        let labels = "location=\"Dyserth\",location_id=\"2650584\",name=\"Dyserth\"";
+        let degrees = WindDirection::parse(&current.wind_direction)
+            .unwrap()
+            .degrees();
+        for line in [
+            "weathema_location_up{location=\"Dyserth\"} 1".to_string(),
+            "weathema_location_up{location=\"Nowhere\"} 0".to_string(),
+            format!("weathema_temperature_celsius{{{}}} {}", labels, current.temperature_c),
+            format!("weathema_wind_direction_degrees{{{}}} {}", labels, degrees),
+            format!("weathema_pressure_hpa{{{}}} {}", labels, current.pressure.unwrap()),
+            "weathema_upstream_requests_total{endpoint=\"locator\"} 2".to_string(),
+            "weathema_upstream_requests_total{endpoint=\"forecast\"} 1".to_string(),
+            "weathema_upstream_errors_total{endpoint=\"forecast\"} 0".to_string(),
+            "weathema_upstream_request_duration_seconds_bucket{endpoint=\"forecast\",le=\"+Inf\"} 1"
+                .to_string(),
+            "weathema_upstream_request_duration_seconds_count{endpoint=\"forecast\"} 1".to_string(),
+        ] {
+            assert!(body.lines().any(|candidate| candidate == line), "{}", line);
+        }
+        assert!(!body.contains("weathema_temperature_celsius{location=\"Nowhere\""));
+    }
+}